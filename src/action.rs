@@ -29,4 +29,16 @@ pub fn default_input_map() -> InputMap<Action> {
         (Action::MoveUp, KeyCode::KeyW),
         (Action::OpenMenu, KeyCode::Escape),
     ])
+    .with(Action::Cancel, GamepadButton::East)
+    .with(Action::CloseMenu, GamepadButton::Start)
+    .with(Action::Confirm, GamepadButton::South)
+    .with(Action::MoveDown, GamepadButton::DPadDown)
+    .with(Action::MoveDown, GamepadControlDirection::LEFT_DOWN)
+    .with(Action::MoveLeft, GamepadButton::DPadLeft)
+    .with(Action::MoveLeft, GamepadControlDirection::LEFT_LEFT)
+    .with(Action::MoveRight, GamepadButton::DPadRight)
+    .with(Action::MoveRight, GamepadControlDirection::LEFT_RIGHT)
+    .with(Action::MoveUp, GamepadButton::DPadUp)
+    .with(Action::MoveUp, GamepadControlDirection::LEFT_UP)
+    .with(Action::OpenMenu, GamepadButton::Start)
 }
@@ -0,0 +1,188 @@
+use bevy::{prelude::*, utils::HashMap};
+use serde::Deserialize;
+
+use crate::asset_handles::AssetHandles;
+use crate::simple_animations::{AnimationSet, SimpleAnimation};
+
+/// A requirement for taking a transition.
+#[derive(Clone, Debug, Deserialize)]
+pub enum Condition {
+    /// The current clip is a one-shot clip that has played through.
+    Finished,
+    /// The entity was hit this frame.
+    Hit,
+    /// The entity is not moving.
+    Idle,
+    /// The entity is moving.
+    Moving,
+    /// The entity's gameplay state has this name.
+    State(String),
+}
+
+impl Condition {
+    fn holds(&self, parameters: &AnimationParameters, animation: &SimpleAnimation) -> bool {
+        match self {
+            Condition::Finished => animation.is_finished(),
+            Condition::Hit => parameters.hit,
+            Condition::Idle => parameters.direction == Vec2::ZERO,
+            Condition::Moving => parameters.direction != Vec2::ZERO,
+            Condition::State(state) => parameters.state == state,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct AnimationStateDef {
+    pub clip: String,
+    /// Mirror the sprite when moving left.
+    #[serde(default)]
+    pub flip_with_direction: bool,
+    /// The state to move on to as soon as this state's one-shot clip finishes, without waiting a
+    /// frame for a transition.
+    #[serde(default)]
+    pub then: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct TransitionDef {
+    /// The states this transition can be taken from, or every state if missing.
+    #[serde(default)]
+    pub from: Option<Vec<String>>,
+    pub to: String,
+    /// Every condition must hold for the transition to be taken.
+    pub when: Vec<Condition>,
+}
+
+impl TransitionDef {
+    fn applies(
+        &self,
+        state: &str,
+        parameters: &AnimationParameters,
+        animation: &SimpleAnimation,
+    ) -> bool {
+        self.to != state
+            && self
+                .from
+                .as_ref()
+                .is_none_or(|from| from.iter().any(|from| from == state))
+            && self
+                .when
+                .iter()
+                .all(|condition| condition.holds(parameters, animation))
+    }
+}
+
+/// A state machine mapping an entity's gameplay state to sprite clips. Transitions are checked in
+/// order, and the first one that applies is taken.
+#[derive(Clone, Debug, Deserialize)]
+pub struct AnimationControllerDef {
+    pub initial: String,
+    pub states: HashMap<String, AnimationStateDef>,
+    pub transitions: Vec<TransitionDef>,
+}
+
+/// Plays clips for the entity according to the named controller in the manifest.
+#[derive(Component)]
+#[require(AnimationParameters, SimpleAnimation)]
+pub struct AnimationController {
+    pub controller: String,
+    pub state: String,
+}
+
+impl AnimationController {
+    pub fn new(controller: impl Into<String>) -> Self {
+        Self {
+            controller: controller.into(),
+            state: String::new(),
+        }
+    }
+}
+
+/// Gameplay values an `AnimationController` reacts to, kept up to date by gameplay systems.
+#[derive(Component, Default)]
+pub struct AnimationParameters {
+    pub direction: Vec2,
+    /// Set for a single frame when the entity is hit.
+    pub hit: bool,
+    pub state: &'static str,
+}
+
+fn drive_animation_controllers(
+    asset_handles: Res<AssetHandles>,
+    mut query: Query<(
+        &mut AnimationController,
+        &mut AnimationParameters,
+        &mut SimpleAnimation,
+        &mut Sprite,
+    )>,
+) {
+    for (mut controller, mut parameters, mut animation, mut sprite) in query.iter_mut() {
+        let Some(definition) = asset_handles.animation_controller(&controller.controller) else {
+            continue;
+        };
+
+        if controller.state.is_empty() {
+            controller.state = definition.initial.clone();
+        }
+
+        // The clip may have already handed over to the one queued after it.
+        if let Some(next) = definition
+            .states
+            .get(&controller.state)
+            .and_then(|state| state.then.as_ref())
+            && definition
+                .states
+                .get(next)
+                .is_some_and(|next_state| animation.clip.name == next_state.clip)
+        {
+            controller.state = next.clone();
+        }
+
+        if let Some(transition) = definition
+            .transitions
+            .iter()
+            .find(|transition| transition.applies(&controller.state, &parameters, &animation))
+        {
+            controller.state = transition.to.clone();
+        }
+
+        if parameters.hit {
+            parameters.hit = false;
+        }
+
+        let Some(state) = definition.states.get(&controller.state) else {
+            error!(
+                "Animation controller \"{}\" has no state \"{}\"",
+                controller.controller, controller.state
+            );
+            continue;
+        };
+
+        if animation.clip.name != state.clip {
+            let clip = asset_handles.animation(&state.clip);
+
+            match state
+                .then
+                .as_ref()
+                .and_then(|next| definition.states.get(next))
+            {
+                Some(next_state) => {
+                    animation.play_then(clip, asset_handles.animation(&next_state.clip));
+                }
+                None => animation.play(clip),
+            }
+        }
+
+        if state.flip_with_direction && parameters.direction.x != 0.0 {
+            sprite.flip_x = parameters.direction.x < 0.0;
+        }
+    }
+}
+
+pub struct AnimationControllerPlugin;
+
+impl Plugin for AnimationControllerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PostUpdate, drive_animation_controllers.in_set(AnimationSet));
+    }
+}
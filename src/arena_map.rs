@@ -0,0 +1,164 @@
+use bevy::{
+    asset::{AssetLoader, LoadContext, io::Reader},
+    prelude::*,
+    utils::HashMap,
+};
+use serde::Deserialize;
+
+/// The largest map that fits on the tilemap arenas are laid out on, in tiles.
+pub const MAX_SIZE: UVec2 = UVec2::new(128, 64);
+
+/// Floor that does something to whatever moves over it.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq)]
+pub enum Hazard {
+    /// Makes the keep turn sluggishly.
+    Ice,
+    /// Sets enemies alight.
+    Lava,
+    /// Slows everything down.
+    Mud,
+    /// Speeds everything up.
+    SpeedPad,
+    /// Hurts everything standing on it.
+    Spikes,
+}
+
+impl Hazard {
+    pub const ALL: [Hazard; 5] = [
+        Hazard::Ice,
+        Hazard::Lava,
+        Hazard::Mud,
+        Hazard::SpeedPad,
+        Hazard::Spikes,
+    ];
+}
+
+/// Something breakable standing on the floor, which blocks the way until it is smashed.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq)]
+pub enum Prop {
+    Crate,
+    Fence,
+    Tree,
+}
+
+impl Prop {
+    pub const ALL: [Prop; 3] = [Prop::Crate, Prop::Fence, Prop::Tree];
+}
+
+/// What a tile in a map is, before it is drawn.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq)]
+pub enum TileKind {
+    Floor,
+    Hazard(Hazard),
+    /// Solid, and drawn on the floor without walls around it.
+    Obstacle,
+    /// Solid until it is smashed, and drawn on the floor without walls around it.
+    Prop(Prop),
+    /// Solid, beyond the arena's walls.
+    Outside,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct TileDef {
+    pub kind: TileKind,
+    /// A tile from `terrain.png` to draw instead of the one autotiling would pick.
+    #[serde(default)]
+    pub texture: Option<u32>,
+}
+
+/// Where the keep starts, in the same column and row coordinates as the map's rows.
+#[derive(Clone, Debug, Deserialize)]
+pub struct KeepStartDef {
+    pub position: (u32, u32),
+    /// The direction the keep sets off in, with y pointing up the screen.
+    pub heading: (f32, f32),
+}
+
+/// A hand-authored arena. Each character of each row is one tile, looked up in the legend, with
+/// the top row first. Maps smaller than the tilemap are placed in its middle, surrounded by
+/// outside tiles.
+#[derive(Asset, Debug, Deserialize, TypePath)]
+pub struct ArenaMap {
+    pub keep: KeepStartDef,
+    pub legend: HashMap<char, TileDef>,
+    pub rows: Vec<String>,
+    /// Points enemies spawn around, in column and row coordinates. If empty, enemies spawn from
+    /// the same zones as in generated arenas.
+    #[serde(default)]
+    pub spawn_points: Vec<(u32, u32)>,
+}
+
+impl ArenaMap {
+    pub fn size(&self) -> UVec2 {
+        UVec2::new(
+            self.rows.first().map_or(0, |row| row.chars().count()) as u32,
+            self.rows.len() as u32,
+        )
+    }
+
+    /// The tile at `column` and `row`, if it is on the map.
+    pub fn tile(&self, column: u32, row: u32) -> Option<&TileDef> {
+        let symbol = self.rows.get(row as usize)?.chars().nth(column as usize)?;
+
+        self.legend.get(&symbol)
+    }
+}
+
+#[derive(Default)]
+pub struct ArenaMapLoader;
+
+impl AssetLoader for ArenaMapLoader {
+    type Asset = ArenaMap;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+
+        reader.read_to_end(&mut bytes).await?;
+
+        let map: ArenaMap = ron::de::from_bytes(&bytes)?;
+        let size = map.size();
+        let width = size.x as usize;
+
+        if size.cmpgt(MAX_SIZE).any() {
+            return Err(format!(
+                "the map is {}×{} tiles, but can be at most {}×{}",
+                size.x, size.y, MAX_SIZE.x, MAX_SIZE.y
+            )
+            .into());
+        }
+
+        for (row_index, row) in map.rows.iter().enumerate() {
+            if row.chars().count() != width {
+                return Err(format!("row {row_index} is not {width} tiles wide").into());
+            }
+
+            if let Some(symbol) = row.chars().find(|symbol| !map.legend.contains_key(symbol)) {
+                return Err(
+                    format!("row {row_index} uses '{symbol}', which isn't in the legend").into(),
+                );
+            }
+        }
+
+        let (column, row) = map.keep.position;
+
+        if map
+            .tile(column, row)
+            .is_none_or(|tile| tile.kind != TileKind::Floor)
+        {
+            return Err(format!("the keep starts at ({column}, {row}), which isn't floor").into());
+        }
+
+        Ok(map)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["arena.ron"]
+    }
+}
@@ -1,61 +1,188 @@
-use bevy::{prelude::*, utils::HashMap};
+use bevy::{asset::UntypedAssetId, prelude::*, utils::HashMap};
 
+use crate::animation_controller::AnimationControllerDef;
 use crate::app_state::AppState;
-
-const FONTS: [(&'static str, &'static str); 1] = [
-    ("default", "fonts/PressStart2P-Regular.ttf"),
-];
-
-const TEXTURE_ATLASES: [(&'static str, &'static str, UVec2, u32, u32, Option<UVec2>, Option<UVec2>); 3] = [
-    ("enemy", "sprites/enemies.png", UVec2::splat(16), 3, 2, Some(UVec2::splat(1)), None),
-    ("player", "sprites/keep-v2.png", UVec2::splat(32), 2, 2, Some(UVec2::splat(1)), None),
-    ("weapon", "sprites/weapons.png", UVec2::splat(8), 2, 2, Some(UVec2::splat(1)), None),
-];
+use crate::arena_map::{ArenaMap, ArenaMapLoader};
+use crate::autotile::AutotileRules;
+use crate::manifest::{MANIFEST_PATH, Manifest, ManifestLoader};
+use crate::simple_animations::SpriteClip;
 
 #[derive(Debug, Default, Resource)]
 pub struct AssetHandles {
-    pub font_map: HashMap<String, Handle<Font>>,
-    pub image_map: HashMap<String, Handle<Image>>,
-    pub texture_atlas_layout_map: HashMap<String, Handle<TextureAtlasLayout>>,
+    animation_controller_map: HashMap<String, AnimationControllerDef>,
+    animation_map: HashMap<String, SpriteClip>,
+    arena_map: HashMap<String, Handle<ArenaMap>>,
+    audio_map: HashMap<String, Handle<AudioSource>>,
+    autotile_rules_map: HashMap<String, AutotileRules>,
+    font_map: HashMap<String, Handle<Font>>,
+    image_map: HashMap<String, Handle<Image>>,
+    manifest: Handle<Manifest>,
+    ready: bool,
+    texture_atlas_layout_map: HashMap<String, Handle<TextureAtlasLayout>>,
+}
+
+/// Looks up `key` in `map`, logging the keys that do exist if it is missing.
+fn lookup_ref<'a, T>(kind: &str, map: &'a HashMap<String, T>, key: &str) -> Option<&'a T> {
+    if let Some(value) = map.get(key) {
+        return Some(value);
+    }
+
+    let mut known: Vec<_> = map.keys().map(String::as_str).collect();
+    known.sort_unstable();
+
+    error!(
+        "Unknown {kind} \"{key}\" (the manifest at {MANIFEST_PATH} defines: {})",
+        known.join(", ")
+    );
+
+    None
+}
+
+fn lookup<T: Clone + Default>(kind: &str, map: &HashMap<String, T>, key: &str) -> T {
+    lookup_ref(kind, map, key).cloned().unwrap_or_default()
+}
+
+impl AssetHandles {
+    pub fn animation_controller(&self, key: &str) -> Option<&AnimationControllerDef> {
+        lookup_ref("animation controller", &self.animation_controller_map, key)
+    }
+
+    pub fn animation(&self, key: &str) -> SpriteClip {
+        lookup("animation", &self.animation_map, key)
+    }
+
+    /// Every hand-authored arena, in the same order every time.
+    pub fn arenas(&self) -> Vec<&Handle<ArenaMap>> {
+        let mut keys: Vec<_> = self.arena_map.keys().collect();
+        keys.sort_unstable();
+
+        keys.into_iter().map(|key| &self.arena_map[key]).collect()
+    }
+
+    pub fn audio(&self, key: &str) -> Handle<AudioSource> {
+        lookup("sound", &self.audio_map, key)
+    }
+
+    pub fn autotile_rules(&self, key: &str) -> Option<&AutotileRules> {
+        lookup_ref("autotile rules", &self.autotile_rules_map, key)
+    }
+
+    pub fn font(&self, key: &str) -> Handle<Font> {
+        lookup("font", &self.font_map, key)
+    }
+
+    pub fn image(&self, key: &str) -> Handle<Image> {
+        lookup("image", &self.image_map, key)
+    }
+
+    /// Whether the manifest has loaded and every asset it lists has been requested.
+    pub fn is_ready(&self) -> bool {
+        self.ready
+    }
+
+    /// Every asset loaded from a file, including the manifest, for checking their load states.
+    /// Texture atlas layouts are built in code, so they are not included.
+    pub fn loaded_ids(&self) -> impl Iterator<Item = UntypedAssetId> + '_ {
+        std::iter::once(self.manifest.id().untyped())
+            .chain(self.arena_map.values().map(|handle| handle.id().untyped()))
+            .chain(self.audio_map.values().map(|handle| handle.id().untyped()))
+            .chain(self.font_map.values().map(|handle| handle.id().untyped()))
+            .chain(self.image_map.values().map(|handle| handle.id().untyped()))
+    }
+
+    /// A sprite's atlas for the sheet `key`, starting at frame `index`.
+    pub fn texture_atlas(&self, key: &str, index: usize) -> TextureAtlas {
+        TextureAtlas {
+            index,
+            layout: lookup("texture atlas", &self.texture_atlas_layout_map, key),
+        }
+    }
 }
 
-fn initialize_asset_handles(
+fn load_manifest(mut asset_handles: ResMut<AssetHandles>, asset_server: Res<AssetServer>) {
+    asset_handles.manifest = asset_server.load(MANIFEST_PATH);
+}
+
+pub fn initialize_asset_handles(
     mut asset_handles: ResMut<AssetHandles>,
     asset_server: Res<AssetServer>,
-    mut next_state: ResMut<NextState<AppState>>,
+    manifests: Res<Assets<Manifest>>,
     mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
 ) {
-    for (key, path) in FONTS {
+    if asset_handles.ready {
+        return;
+    }
+
+    let Some(manifest) = manifests.get(&asset_handles.manifest) else {
+        return;
+    };
+
+    asset_handles.animation_controller_map = manifest.animation_controllers.clone();
+    asset_handles.autotile_rules_map = manifest.autotile_rules.clone();
+
+    for (key, clip) in manifest.animations.iter() {
+        let clip = SpriteClip {
+            name: key.clone(),
+            ..clip.clone()
+        };
+
+        asset_handles.animation_map.insert(key.clone(), clip);
+    }
+
+    for (key, path) in manifest.arenas.iter() {
+        let arena_handle = asset_server.load(path);
+
+        asset_handles.arena_map.insert(key.clone(), arena_handle);
+    }
+
+    for (key, path) in manifest.fonts.iter() {
         let font_handle = asset_server.load(path);
-    
-        asset_handles
-            .font_map
-            .insert(key.to_string(), font_handle);
+
+        asset_handles.font_map.insert(key.clone(), font_handle);
     }
 
-    for (key, path, tile_size, columns, rows, padding, offset) in TEXTURE_ATLASES {
-        let texture_handle = asset_server.load(path);
-    
-        asset_handles
-            .image_map
-            .insert(key.to_string(), texture_handle);
-    
-        let texture_atlas_layout = TextureAtlasLayout::from_grid(tile_size, columns, rows, padding, offset);
-        let texture_atlas_layout_handle = texture_atlas_layouts.add(texture_atlas_layout);
-    
+    for (key, path) in manifest.images.iter() {
+        let image_handle = asset_server.load(path);
+
+        asset_handles.image_map.insert(key.clone(), image_handle);
+    }
+
+    // Music is only played in native builds.
+    #[cfg(not(target_family = "wasm"))]
+    for (key, path) in manifest.sounds.iter() {
+        let audio_handle = asset_server.load(path);
+
+        asset_handles.audio_map.insert(key.clone(), audio_handle);
+    }
+
+    for (key, texture_atlas) in manifest.texture_atlases.iter() {
+        let texture_handle = asset_server.load(&texture_atlas.path);
+
+        asset_handles.image_map.insert(key.clone(), texture_handle);
+
+        let texture_atlas_layout_handle = texture_atlas_layouts.add(texture_atlas.layout());
+
         asset_handles
             .texture_atlas_layout_map
-            .insert(key.to_string(), texture_atlas_layout_handle);
+            .insert(key.clone(), texture_atlas_layout_handle);
     }
 
-    next_state.set(AppState::Menu);
+    asset_handles.ready = true;
 }
 
 pub struct AssetHandlesPlugin;
 
 impl Plugin for AssetHandlesPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(AppState::Loading), initialize_asset_handles);
+        app.init_asset::<ArenaMap>();
+        app.init_asset::<Manifest>();
+        app.init_asset_loader::<ArenaMapLoader>();
+        app.init_asset_loader::<ManifestLoader>();
+        app.add_systems(OnEnter(AppState::Loading), load_manifest);
+        app.add_systems(
+            Update,
+            initialize_asset_handles.run_if(in_state(AppState::Loading)),
+        );
         app.init_resource::<AssetHandles>();
     }
 }
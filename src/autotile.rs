@@ -0,0 +1,106 @@
+use bevy::prelude::*;
+use rand::{Rng, seq::SliceRandom};
+use serde::Deserialize;
+
+/// A tile in the 3×3 block around the one being drawn, with north pointing up the screen.
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub enum Neighbour {
+    /// The tile being drawn itself.
+    Centre,
+    E,
+    N,
+    NE,
+    NW,
+    S,
+    SE,
+    SW,
+    W,
+}
+
+impl Neighbour {
+    const ALL: [Neighbour; 9] = [
+        Neighbour::Centre,
+        Neighbour::E,
+        Neighbour::N,
+        Neighbour::NE,
+        Neighbour::NW,
+        Neighbour::S,
+        Neighbour::SE,
+        Neighbour::SW,
+        Neighbour::W,
+    ];
+
+    fn bit(&self) -> u16 {
+        1 << *self as u16
+    }
+
+    fn mask(neighbours: &[Neighbour]) -> u16 {
+        neighbours
+            .iter()
+            .fold(0, |mask, neighbour| mask | neighbour.bit())
+    }
+
+    fn offset(&self) -> IVec2 {
+        match self {
+            Neighbour::Centre => IVec2::ZERO,
+            Neighbour::E => IVec2::X,
+            Neighbour::N => IVec2::Y,
+            Neighbour::NE => IVec2::new(1, 1),
+            Neighbour::NW => IVec2::new(-1, 1),
+            Neighbour::S => IVec2::NEG_Y,
+            Neighbour::SE => IVec2::new(1, -1),
+            Neighbour::SW => IVec2::new(-1, -1),
+            Neighbour::W => IVec2::NEG_X,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct AutotileRule {
+    /// Neighbours that must be solid. Any not listed here or in `empty` can be either.
+    #[serde(default)]
+    pub solid: Vec<Neighbour>,
+    #[serde(default)]
+    pub empty: Vec<Neighbour>,
+    /// Tile indices to pick from at random when the rule matches, for variety.
+    pub tiles: Vec<u32>,
+}
+
+impl AutotileRule {
+    fn matches(&self, mask: u16) -> bool {
+        let solid = Neighbour::mask(&self.solid);
+
+        mask & solid == solid && mask & Neighbour::mask(&self.empty) == 0
+    }
+}
+
+/// Chooses tiles from a sheet by which of their neighbours are solid, so walls and corners
+/// follow whatever shape the solid tiles make.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct AutotileRules {
+    /// Checked in order, and the first that matches picks the tile.
+    pub rules: Vec<AutotileRule>,
+}
+
+impl AutotileRules {
+    /// The tile index for `point`, given which points are `solid`. Falls back to the first tile
+    /// in the sheet if no rule matches.
+    pub fn texture_index(
+        &self,
+        point: IVec2,
+        solid: impl Fn(IVec2) -> bool,
+        rng: &mut impl Rng,
+    ) -> u32 {
+        let mask = Neighbour::ALL
+            .iter()
+            .filter(|neighbour| solid(point + neighbour.offset()))
+            .fold(0, |mask, neighbour| mask | neighbour.bit());
+
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(mask))
+            .and_then(|rule| rule.tiles.choose(rng))
+            .copied()
+            .unwrap_or_default()
+    }
+}
@@ -1,4 +1,8 @@
 use bevy::prelude::*;
 
 pub const DARK_GRAY: Color = Color::srgb(0.47, 0.47, 0.47);
-// pub const LIME_GREEN: Color = Color::srgb(0.44, 0.95, 0.25);
+pub const DARK_RED: Color = Color::srgb(0.55, 0.08, 0.08);
+pub const GOLD: Color = Color::srgb(1.0, 0.8, 0.2);
+pub const ICE_BLUE: Color = Color::srgb(0.45, 0.75, 1.0);
+pub const LIME_GREEN: Color = Color::srgb(0.44, 0.95, 0.25);
+pub const ORANGE: Color = Color::srgb(1.0, 0.5, 0.1);
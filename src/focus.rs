@@ -0,0 +1,260 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+use leafwing_input_manager::prelude::*;
+
+use crate::action::Action;
+use crate::colors::DARK_GRAY;
+
+/// Marks a UI node that can be reached with keyboard or gamepad navigation.
+#[derive(Component, Default)]
+pub struct Focusable;
+
+/// Marks a focusable that handles `MoveLeft` and `MoveRight` itself rather than moving focus.
+#[derive(Component, Default)]
+pub struct HorizontalInput;
+
+/// Marks the focusable that should receive focus when nothing else has it.
+#[derive(Component)]
+pub struct AutoFocus;
+
+/// Marks a UI root, such as a modal, that keeps focus to itself while it is open. Only the
+/// topmost one counts, so focusables behind it can't be reached.
+#[derive(Component)]
+pub struct FocusTrap;
+
+#[derive(Debug, Default, Resource)]
+pub struct Focus(pub Option<Entity>);
+
+/// Sent when a focusable is activated, either with `Action::Confirm` or a mouse press.
+#[derive(Event)]
+pub struct FocusActivated(pub Entity);
+
+/// Sent when `Action::Cancel` is pressed, so the current screen can go back.
+#[derive(Event)]
+pub struct FocusCancelled;
+
+const NAVIGATION_DIRECTIONS: [(Action, Vec2); 4] = [
+    // UI space grows downwards.
+    (Action::MoveDown, Vec2::Y),
+    (Action::MoveLeft, Vec2::NEG_X),
+    (Action::MoveRight, Vec2::X),
+    (Action::MoveUp, Vec2::NEG_Y),
+];
+
+/// Decides which focusables can take focus, given the focus traps that are open.
+#[derive(SystemParam)]
+struct FocusLayer<'w, 's> {
+    parent_query: Query<'w, 's, &'static Parent>,
+    trap_query: Query<'w, 's, (Entity, &'static ComputedNode), With<FocusTrap>>,
+}
+
+impl FocusLayer<'_, '_> {
+    /// Whether `entity` is inside the topmost focus trap, or there isn't one.
+    fn allows(&self, entity: Entity) -> bool {
+        let Some((trap, _)) = self
+            .trap_query
+            .iter()
+            .max_by_key(|(_, node)| node.stack_index())
+        else {
+            return true;
+        };
+
+        entity == trap
+            || self
+                .parent_query
+                .iter_ancestors(entity)
+                .any(|ancestor| ancestor == trap)
+    }
+}
+
+/// Scores how well a focusable at `offset` from the current one matches `direction`. Lower is
+/// better, and anything behind the current focusable is not a candidate at all.
+fn navigation_score(offset: Vec2, direction: Vec2) -> Option<f32> {
+    let along = offset.dot(direction);
+
+    if along <= 0.0 {
+        return None;
+    }
+
+    Some(along + offset.perp_dot(direction).abs() * 2.0)
+}
+
+fn acquire_focus(
+    auto_focus_query: Query<Entity, (With<AutoFocus>, With<Focusable>)>,
+    mut focus: ResMut<Focus>,
+    focus_layer: FocusLayer,
+    focusable_query: Query<(Entity, &GlobalTransform), With<Focusable>>,
+) {
+    if focus
+        .0
+        .is_some_and(|entity| focusable_query.contains(entity) && focus_layer.allows(entity))
+    {
+        return;
+    }
+
+    let next_focus = auto_focus_query
+        .iter()
+        .find(|entity| focus_layer.allows(*entity))
+        .or_else(|| {
+            // Fall back to the top-left-most focusable.
+            focusable_query
+                .iter()
+                .filter(|(entity, _)| focus_layer.allows(*entity))
+                .min_by(|(_, a), (_, b)| {
+                    let a = a.translation();
+                    let b = b.translation();
+
+                    a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x))
+                })
+                .map(|(entity, _)| entity)
+        });
+
+    if focus.0 != next_focus {
+        focus.0 = next_focus;
+    }
+}
+
+fn hover_focus(
+    mut activated_events: EventWriter<FocusActivated>,
+    mut focus: ResMut<Focus>,
+    focus_layer: FocusLayer,
+    query: Query<(Entity, &Interaction), (Changed<Interaction>, With<Focusable>)>,
+) {
+    for (entity, interaction) in query.iter() {
+        if !focus_layer.allows(entity) {
+            continue;
+        }
+
+        match *interaction {
+            Interaction::Hovered => {
+                focus.0 = Some(entity);
+            }
+            Interaction::Pressed => {
+                focus.0 = Some(entity);
+                activated_events.send(FocusActivated(entity));
+            }
+            Interaction::None => {}
+        }
+    }
+}
+
+fn navigate_focus(
+    action_state: Res<ActionState<Action>>,
+    mut focus: ResMut<Focus>,
+    focus_layer: FocusLayer,
+    focusable_query: Query<(Entity, &GlobalTransform), With<Focusable>>,
+    horizontal_input_query: Query<(), With<HorizontalInput>>,
+) {
+    let Some(current) = focus.0 else {
+        return;
+    };
+
+    let Ok((_, current_transform)) = focusable_query.get(current) else {
+        return;
+    };
+
+    let captures_horizontal = horizontal_input_query.contains(current);
+
+    for (action, direction) in NAVIGATION_DIRECTIONS {
+        if !action_state.just_pressed(&action) {
+            continue;
+        }
+
+        if captures_horizontal && direction.x != 0.0 {
+            continue;
+        }
+
+        let origin = current_transform.translation().truncate();
+
+        let next_focus = focusable_query
+            .iter()
+            .filter(|(entity, _)| *entity != current && focus_layer.allows(*entity))
+            .filter_map(|(entity, transform)| {
+                let offset = transform.translation().truncate() - origin;
+
+                navigation_score(offset, direction).map(|score| (entity, score))
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b));
+
+        if let Some((entity, _)) = next_focus {
+            focus.0 = Some(entity);
+        }
+
+        return;
+    }
+}
+
+fn confirm_focus(
+    action_state: Res<ActionState<Action>>,
+    mut activated_events: EventWriter<FocusActivated>,
+    mut cancelled_events: EventWriter<FocusCancelled>,
+    focus: Res<Focus>,
+) {
+    if action_state.just_pressed(&Action::Confirm)
+        && let Some(entity) = focus.0
+    {
+        activated_events.send(FocusActivated(entity));
+    }
+
+    if action_state.just_pressed(&Action::Cancel) && focus.0.is_some() {
+        cancelled_events.send(FocusCancelled);
+    }
+}
+
+fn style_focusables(
+    focus: Res<Focus>,
+    mut button_query: Query<
+        (
+            Entity,
+            &mut BackgroundColor,
+            &mut BorderColor,
+            &Children,
+            &Interaction,
+        ),
+        (With<Button>, With<Focusable>),
+    >,
+    mut text_query: Query<&mut TextColor>,
+) {
+    for (entity, mut background_color, mut border_color, children, interaction) in
+        button_query.iter_mut()
+    {
+        let Ok(mut text_color) = text_query.get_mut(children[0]) else {
+            continue;
+        };
+
+        let (background, border, text) = match *interaction {
+            Interaction::Pressed => (Color::WHITE, Color::WHITE, Color::BLACK),
+            _ if focus.0 == Some(entity) || *interaction == Interaction::Hovered => {
+                (Color::BLACK, DARK_GRAY, DARK_GRAY)
+            }
+            _ => (Color::BLACK, Color::WHITE, Color::WHITE),
+        };
+
+        background_color.set_if_neq(BackgroundColor(background));
+        border_color.set_if_neq(BorderColor(border));
+
+        if text_color.0 != text {
+            text_color.0 = text;
+        }
+    }
+}
+
+pub struct FocusPlugin;
+
+impl Plugin for FocusPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<FocusActivated>();
+        app.add_event::<FocusCancelled>();
+        app.add_systems(
+            Update,
+            (
+                hover_focus,
+                acquire_focus,
+                navigate_focus,
+                confirm_focus,
+                style_focusables,
+            )
+                .chain(),
+        );
+        app.init_resource::<Focus>();
+    }
+}
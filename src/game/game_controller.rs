@@ -1,6 +1,44 @@
 use bevy::prelude::*;
 
-#[derive(Default, Resource)]
+pub const DEFENDER_HEALTH: u32 = 5;
+const STARTING_CREW: usize = 2;
+
+/// A defender on the keep's crew, kept between waves.
+#[derive(Clone)]
+pub struct CrewMember {
+    /// Knocked out, and sits out every wave until revived in the shop.
+    pub down: bool,
+    pub health: u32,
+    pub max_health: u32,
+}
+
+impl Default for CrewMember {
+    fn default() -> Self {
+        Self {
+            down: false,
+            health: DEFENDER_HEALTH,
+            max_health: DEFENDER_HEALTH,
+        }
+    }
+}
+
+#[derive(Resource)]
 pub struct GameController {
+    pub crew: Vec<CrewMember>,
+    pub gold: u32,
+    /// Seeds everything about a run that should come out the same when replayed, such as the
+    /// arena layouts.
+    pub seed: u64,
     pub wave_level: u32
-}
\ No newline at end of file
+}
+
+impl Default for GameController {
+    fn default() -> Self {
+        Self {
+            crew: vec![CrewMember::default(); STARTING_CREW],
+            gold: 0,
+            seed: 0,
+            wave_level: 0,
+        }
+    }
+}
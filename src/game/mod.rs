@@ -7,24 +7,50 @@ mod shop;
 mod wave;
 
 use bevy::prelude::*;
+use bevy_prng::WyRand;
+use bevy_rand::prelude::*;
 use game_controller::GameController;
 use game_sets::PausableSet;
 use game_state::GameState;
+use pause_menu::PauseMenuPlugin;
 use pause_state::PauseState;
 use shop::ShopPlugin;
 use wave::WavePlugin;
 
+use crate::app_state::AppState;
+use crate::game_mode::GameMode;
+use crate::simple_animations::AnimationSet;
+use rand_core::RngCore;
+
+/// Every new game starts from the first wave with nothing in the bank, on a fresh seed.
+fn reset_game_controller(mut commands: Commands, mut global_rng: GlobalEntropy<WyRand>) {
+    commands.insert_resource(GameController {
+        seed: global_rng.next_u64(),
+        ..default()
+    });
+}
+
 pub struct GamePlugin;
 
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins((ShopPlugin, WavePlugin));
+        app.add_plugins((PauseMenuPlugin, ShopPlugin, WavePlugin));
 
         app.add_sub_state::<GameState>();
         app.add_sub_state::<PauseState>();
 
         app.configure_sets(Update, PausableSet.run_if(in_state(PauseState::Running)));
+        app.configure_sets(
+            PreUpdate,
+            AnimationSet.run_if(not(in_state(PauseState::Paused))),
+        );
+        app.configure_sets(
+            PostUpdate,
+            AnimationSet.run_if(not(in_state(PauseState::Paused))),
+        );
 
+        app.add_systems(OnEnter(AppState::Game), reset_game_controller);
         app.init_resource::<GameController>();
+        app.init_resource::<GameMode>();
     }
 }
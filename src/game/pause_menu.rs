@@ -1 +1,124 @@
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
 
+use crate::{
+    action::Action,
+    app_state::AppState,
+    asset_handles::AssetHandles,
+    focus::{AutoFocus, FocusActivated, FocusCancelled},
+    settings::{MAX_VOLUME, Settings},
+    widgets::{self, Slider, Toggle},
+};
+
+use super::pause_state::PauseState;
+
+#[derive(Component)]
+struct MusicToggle;
+
+#[derive(Component)]
+struct PauseMenu;
+
+#[derive(Component)]
+struct QuitButton;
+
+#[derive(Component)]
+struct ResumeButton;
+
+#[derive(Component)]
+struct VolumeSlider;
+
+fn close_pause_menu(
+    action_state: Res<ActionState<Action>>,
+    mut activated_events: EventReader<FocusActivated>,
+    mut cancelled_events: EventReader<FocusCancelled>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+    quit_button_query: Query<(), With<QuitButton>>,
+    resume_button_query: Query<(), With<ResumeButton>>,
+) {
+    if action_state.just_pressed(&Action::CloseMenu) || cancelled_events.read().count() > 0 {
+        next_pause_state.set(PauseState::Running);
+    }
+
+    for FocusActivated(entity) in activated_events.read() {
+        if resume_button_query.contains(*entity) {
+            next_pause_state.set(PauseState::Running);
+        }
+
+        if quit_button_query.contains(*entity) {
+            next_app_state.set(AppState::Menu);
+        }
+    }
+}
+
+fn destroy_pause_menu(mut commands: Commands, query: Query<Entity, With<PauseMenu>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn open_pause_menu(
+    action_state: Res<ActionState<Action>>,
+    mut next_state: ResMut<NextState<PauseState>>,
+) {
+    if action_state.just_pressed(&Action::OpenMenu) {
+        next_state.set(PauseState::Paused);
+    }
+}
+
+fn pause_menu_settings(
+    music_query: Query<&Toggle, (Changed<Toggle>, With<MusicToggle>)>,
+    mut settings: ResMut<Settings>,
+    volume_query: Query<&Slider, (Changed<Slider>, With<VolumeSlider>)>,
+) {
+    if let Ok(toggle) = music_query.get_single() {
+        settings.music = toggle.value;
+    }
+
+    if let Ok(slider) = volume_query.get_single() {
+        settings.volume = slider.value;
+    }
+}
+
+fn setup_pause_menu(
+    asset_handles: Res<AssetHandles>,
+    mut commands: Commands,
+    settings: Res<Settings>,
+) {
+    widgets::modal(&mut commands, &asset_handles, "Paused", |parent| {
+        widgets::list(parent).with_children(|parent| {
+            widgets::button(parent, &asset_handles, "Resume").insert((AutoFocus, ResumeButton));
+            widgets::slider(
+                parent,
+                &asset_handles,
+                Slider {
+                    label: "Volume".to_string(),
+                    max: MAX_VOLUME,
+                    min: 0.0,
+                    step: 1.0,
+                    value: settings.volume,
+                },
+            )
+            .insert(VolumeSlider);
+            widgets::toggle(parent, &asset_handles, "Music", settings.music).insert(MusicToggle);
+            widgets::button(parent, &asset_handles, "Quit").insert(QuitButton);
+        });
+    })
+    .insert(PauseMenu);
+}
+
+pub struct PauseMenuPlugin;
+
+impl Plugin for PauseMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(PauseState::Paused), setup_pause_menu);
+        app.add_systems(OnExit(PauseState::Paused), destroy_pause_menu);
+        app.add_systems(
+            Update,
+            (
+                open_pause_menu.run_if(in_state(PauseState::Running)),
+                (close_pause_menu, pause_menu_settings).run_if(in_state(PauseState::Paused)),
+            ),
+        );
+    }
+}
@@ -1,20 +1,177 @@
 use bevy::prelude::*;
 
+use crate::{
+    asset_handles::AssetHandles,
+    focus::{AutoFocus, FocusActivated},
+    widgets,
+};
+
 use super::{game_controller::GameController, game_state::GameState};
 
+/// Gold per point of health patched up on a wounded defender.
+const REPAIR_COST: u32 = 1;
+/// Gold to get a knocked out defender back on their feet, at full health.
+const REVIVE_COST: u32 = 5;
+
 pub struct ShopPlugin;
 
 #[derive(Component)]
-#[require(Transform, Visibility)]
-struct Shop;
+enum ShopButton {
+    Continue,
+    Repair(usize),
+    Revive(usize),
+}
+
+#[derive(Component)]
+struct ShopMenu;
+
+fn destroy_shop(mut commands: Commands, query: Query<Entity, With<ShopMenu>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn setup_shop(
+    asset_handles: Res<AssetHandles>,
+    mut commands: Commands,
+    game_controller: Res<GameController>,
+) {
+    spawn_shop_menu(&mut commands, &asset_handles, &game_controller, None);
+}
+
+/// Buying something rebuilds the menu, so the prices and gold on show stay up to date, with focus
+/// kept on the row that was bought for.
+fn shop_buttons(
+    asset_handles: Res<AssetHandles>,
+    button_query: Query<&ShopButton>,
+    mut commands: Commands,
+    mut activated_events: EventReader<FocusActivated>,
+    mut game_controller: ResMut<GameController>,
+    menu_query: Query<Entity, With<ShopMenu>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for FocusActivated(entity) in activated_events.read() {
+        let Ok(button) = button_query.get(*entity) else {
+            continue;
+        };
+
+        let index = match button {
+            ShopButton::Continue => {
+                game_controller.wave_level += 1;
+                next_state.set(GameState::Wave);
+                return;
+            }
+            ShopButton::Repair(index) => {
+                let member = &game_controller.crew[*index];
+                let cost = (member.max_health - member.health) * REPAIR_COST;
+
+                if game_controller.gold < cost {
+                    continue;
+                }
+
+                game_controller.gold -= cost;
+                game_controller.crew[*index].health = game_controller.crew[*index].max_health;
+                *index
+            }
+            ShopButton::Revive(index) => {
+                if game_controller.gold < REVIVE_COST {
+                    continue;
+                }
+
+                game_controller.gold -= REVIVE_COST;
+
+                let member = &mut game_controller.crew[*index];
+
+                member.down = false;
+                member.health = member.max_health;
+                *index
+            }
+        };
+
+        for menu_entity in menu_query.iter() {
+            commands.entity(menu_entity).despawn_recursive();
+        }
+
+        spawn_shop_menu(&mut commands, &asset_handles, &game_controller, Some(index));
+        return;
+    }
+}
+
+/// Focus starts on the `focused` defender's row, or the next one down with something to buy, and
+/// otherwise on Continue.
+fn spawn_shop_menu(
+    commands: &mut Commands,
+    asset_handles: &AssetHandles,
+    game_controller: &GameController,
+    focused: Option<usize>,
+) {
+    let crew = &game_controller.crew;
+    let auto_focus = focused.and_then(|focused| {
+        (focused..crew.len())
+            .find(|index| crew[*index].down || crew[*index].health < crew[*index].max_health)
+    });
+
+    widgets::modal(commands, asset_handles, "Between Waves", |parent| {
+        widgets::label(
+            parent,
+            asset_handles,
+            format!("Gold: {}", game_controller.gold),
+        );
+        widgets::list(parent).with_children(|parent| {
+            for (index, member) in crew.iter().enumerate() {
+                let name = format!("Defender {}", index + 1);
+
+                let (mut button, shop_button) = if member.down {
+                    (
+                        widgets::button(
+                            parent,
+                            asset_handles,
+                            format!("Revive {} ({}g)", name, REVIVE_COST),
+                        ),
+                        ShopButton::Revive(index),
+                    )
+                } else if member.health < member.max_health {
+                    let cost = (member.max_health - member.health) * REPAIR_COST;
+
+                    (
+                        widgets::button(
+                            parent,
+                            asset_handles,
+                            format!(
+                                "Repair {} {}/{} ({}g)",
+                                name, member.health, member.max_health, cost
+                            ),
+                        ),
+                        ShopButton::Repair(index),
+                    )
+                } else {
+                    widgets::label(parent, asset_handles, format!("{} ready", name));
+                    continue;
+                };
+
+                button.insert(shop_button);
+
+                if auto_focus == Some(index) {
+                    button.insert(AutoFocus);
+                }
+            }
+
+            let mut continue_button = widgets::button(parent, asset_handles, "Continue");
+
+            continue_button.insert(ShopButton::Continue);
 
-fn setup_shop(mut game_controller: ResMut<GameController>, mut next_state: ResMut<NextState<GameState>>) {
-    game_controller.wave_level += 1;
-    next_state.set(GameState::Wave);
+            if auto_focus.is_none() {
+                continue_button.insert(AutoFocus);
+            }
+        });
+    })
+    .insert(ShopMenu);
 }
 
 impl Plugin for ShopPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(OnEnter(GameState::Shop), setup_shop);
+        app.add_systems(OnExit(GameState::Shop), destroy_shop);
+        app.add_systems(Update, shop_buttons.run_if(in_state(GameState::Shop)));
     }
 }
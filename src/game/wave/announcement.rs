@@ -0,0 +1,205 @@
+use std::cmp::Reverse;
+
+use bevy::prelude::*;
+
+use crate::{
+    asset_handles::AssetHandles,
+    colors::{DARK_RED, GOLD},
+    game::{game_sets::PausableSet, game_state::GameState},
+    widgets,
+};
+
+const DEFAULT_DURATION: f32 = 2.0;
+const FADE_RATE: f32 = 0.15;
+const SLIDE_DISTANCE: f32 = 5.0;
+
+#[derive(Clone, Copy, Default)]
+pub enum AnnouncementStyle {
+    Danger,
+    #[default]
+    Info,
+    Warning,
+}
+
+impl AnnouncementStyle {
+    /// The background and text colours of the banner.
+    fn colors(self) -> (Color, Color) {
+        match self {
+            AnnouncementStyle::Danger => (DARK_RED, Color::WHITE),
+            AnnouncementStyle::Info => (Color::BLACK, Color::WHITE),
+            AnnouncementStyle::Warning => (Color::BLACK, GOLD),
+        }
+    }
+}
+
+/// A message shown in a banner in the middle of the screen. Announcements are shown one at a time,
+/// highest priority first, and a higher priority announcement cuts the current one short.
+#[derive(Clone, Event)]
+pub struct Announcement {
+    pub duration: f32,
+    pub message: String,
+    pub priority: u32,
+    pub style: AnnouncementStyle,
+}
+
+impl Announcement {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            duration: DEFAULT_DURATION,
+            message: message.into(),
+            priority: 0,
+            style: AnnouncementStyle::default(),
+        }
+    }
+
+    pub fn with_duration(mut self, duration: f32) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    pub fn with_priority(mut self, priority: u32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn with_style(mut self, style: AnnouncementStyle) -> Self {
+        self.style = style;
+        self
+    }
+}
+
+#[derive(Component)]
+struct AnnouncementBanner {
+    priority: u32,
+    style: AnnouncementStyle,
+    timer: Timer,
+}
+
+#[derive(Default, Resource)]
+struct AnnouncementQueue(Vec<Announcement>);
+
+fn animate_announcements(
+    mut banner_query: Query<(
+        &AnnouncementBanner,
+        &mut BackgroundColor,
+        &Children,
+        Entity,
+        &mut Node,
+    )>,
+    mut commands: Commands,
+    mut text_query: Query<&mut TextColor>,
+) {
+    for (banner, mut background_color, children, entity, mut node) in banner_query.iter_mut() {
+        if banner.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        // Fade and slide in at the start, and back out at the end.
+        let alpha = (banner
+            .timer
+            .elapsed_secs()
+            .min(banner.timer.remaining_secs())
+            / FADE_RATE)
+            .min(1.0);
+        let (background, text) = banner.style.colors();
+
+        background_color.0 = background.with_alpha(alpha);
+        node.top = Val::Percent(50.0 - SLIDE_DISTANCE * (1.0 - alpha));
+
+        if let Ok(mut text_color) = text_query.get_mut(children[0]) {
+            text_color.0 = text.with_alpha(alpha);
+        }
+    }
+}
+
+fn clear_announcements(
+    banner_query: Query<Entity, With<AnnouncementBanner>>,
+    mut commands: Commands,
+    mut queue: ResMut<AnnouncementQueue>,
+) {
+    queue.0.clear();
+
+    for entity in banner_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn queue_announcements(
+    mut announcement_events: EventReader<Announcement>,
+    mut queue: ResMut<AnnouncementQueue>,
+) {
+    if announcement_events.is_empty() {
+        return;
+    }
+
+    queue.0.extend(announcement_events.read().cloned());
+
+    // Stable, so announcements of equal priority keep their order.
+    queue
+        .0
+        .sort_by_key(|announcement| Reverse(announcement.priority));
+}
+
+fn show_announcements(
+    asset_handles: Res<AssetHandles>,
+    mut banner_query: Query<&mut AnnouncementBanner>,
+    mut commands: Commands,
+    mut queue: ResMut<AnnouncementQueue>,
+) {
+    let Some(next) = queue.0.first() else {
+        return;
+    };
+
+    if let Ok(mut banner) = banner_query.get_single_mut() {
+        // Cut the current banner short, leaving it just enough time to fade out.
+        if next.priority > banner.priority && banner.timer.remaining_secs() > FADE_RATE {
+            let elapsed = banner
+                .timer
+                .duration()
+                .saturating_sub(std::time::Duration::from_secs_f32(FADE_RATE));
+
+            banner.timer.set_elapsed(elapsed);
+        }
+
+        return;
+    }
+
+    let announcement = queue.0.remove(0);
+
+    widgets::banner(&mut commands, &asset_handles, announcement.message).insert(
+        AnnouncementBanner {
+            priority: announcement.priority,
+            style: announcement.style,
+            timer: Timer::from_seconds(announcement.duration, TimerMode::Once),
+        },
+    );
+}
+
+/// Only the time a banner stays up is paused, so announcements made while paused aren't lost.
+fn tick_announcements(mut banner_query: Query<&mut AnnouncementBanner>, time: Res<Time>) {
+    for mut banner in banner_query.iter_mut() {
+        banner.timer.tick(time.delta());
+    }
+}
+
+pub struct AnnouncementPlugin;
+
+impl Plugin for AnnouncementPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Announcement>();
+        app.add_systems(OnExit(GameState::Wave), clear_announcements);
+        app.add_systems(
+            Update,
+            (
+                queue_announcements,
+                show_announcements,
+                tick_announcements.in_set(PausableSet),
+                animate_announcements,
+            )
+                .chain()
+                .run_if(in_state(GameState::Wave)),
+        );
+        app.init_resource::<AnnouncementQueue>();
+    }
+}
@@ -0,0 +1,575 @@
+use std::{collections::VecDeque, ops::RangeInclusive};
+
+use bevy::{math::bounding::*, prelude::*, utils::HashMap};
+use bevy_ecs_tilemap::prelude::*;
+use bevy_prng::WyRand;
+use rand::{Rng, seq::SliceRandom};
+use rand_core::SeedableRng;
+
+use crate::arena_map::{self, ArenaMap, Hazard, Prop, TileKind};
+use crate::autotile::AutotileRules;
+
+/// The size of the tilemap arenas are carved out of, in tiles.
+pub const AREA_SIZE: UVec2 = arena_map::MAX_SIZE;
+pub const TILE_SIZE: f32 = 16.0;
+
+/// Every this many waves, one of the hand-authored arenas is used instead of a generated one.
+const AUTHORED_ARENA_INTERVAL: u32 = 3;
+const CORRIDOR_HALF_WIDTH: RangeInclusive<i32> = 3..=4;
+const FENCE_LENGTH: RangeInclusive<i32> = 3..=5;
+const HAZARD_ATTEMPTS: u32 = 32;
+const HAZARD_PATCH_SIZE: RangeInclusive<i32> = 2..=3;
+const HAZARDS_PER_LEVEL: u32 = 1;
+/// The keep turns about 70 units wide at full speed, so it needs a clear circle this many tiles
+/// across (and a bit more for its own size) to loop around in without hitting anything.
+pub const LOOP_SPACE_RADIUS: f32 = 6.0;
+const MAX_HAZARDS: u32 = 8;
+const MAX_HEIGHT: u32 = 32;
+const MAX_OBSTACLES: u32 = 16;
+const MAX_PROPS: u32 = 16;
+const MAX_WIDTH: u32 = 56;
+const MIN_HEIGHT: u32 = 20;
+const MIN_WIDTH: u32 = 36;
+const OBSTACLE_ATTEMPTS: u32 = 64;
+const OBSTACLES_PER_LEVEL: u32 = 2;
+const PROP_ATTEMPTS: u32 = 32;
+const PROPS_PER_LEVEL: u32 = 1;
+const STARTING_OBSTACLES: u32 = 4;
+const STARTING_PROPS: u32 = 6;
+
+const CRATE: u32 = 26;
+const FENCE: u32 = 27;
+const ICE: u32 = 22;
+const LAVA: u32 = 25;
+const MUD: u32 = 21;
+const OBSTACLE: u32 = 16;
+const SPEED_PAD: u32 = 24;
+const SPIKES: u32 = 23;
+const TREE: u32 = 28;
+
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub enum ArenaTile {
+    Floor,
+    /// Floor with an effect on whatever moves over it.
+    Hazard(Hazard),
+    /// A rock in the middle of the floor.
+    Obstacle,
+    /// Something breakable on the floor.
+    Prop(Prop),
+    /// Beyond the arena's walls.
+    Outside,
+}
+
+impl ArenaTile {
+    pub fn is_solid(&self) -> bool {
+        matches!(
+            self,
+            ArenaTile::Obstacle | ArenaTile::Prop(_) | ArenaTile::Outside
+        )
+    }
+
+    /// The tile from `terrain.png` this is always drawn with, if it isn't autotiled.
+    pub fn texture(&self) -> Option<TileTextureIndex> {
+        let index = match self {
+            ArenaTile::Floor | ArenaTile::Outside => return None,
+            ArenaTile::Hazard(Hazard::Ice) => ICE,
+            ArenaTile::Hazard(Hazard::Lava) => LAVA,
+            ArenaTile::Hazard(Hazard::Mud) => MUD,
+            ArenaTile::Hazard(Hazard::SpeedPad) => SPEED_PAD,
+            ArenaTile::Hazard(Hazard::Spikes) => SPIKES,
+            ArenaTile::Obstacle => OBSTACLE,
+            ArenaTile::Prop(Prop::Crate) => CRATE,
+            ArenaTile::Prop(Prop::Fence) => FENCE,
+            ArenaTile::Prop(Prop::Tree) => TREE,
+        };
+
+        Some(TileTextureIndex(index))
+    }
+}
+
+impl From<TileKind> for ArenaTile {
+    fn from(kind: TileKind) -> Self {
+        match kind {
+            TileKind::Floor => ArenaTile::Floor,
+            TileKind::Hazard(hazard) => ArenaTile::Hazard(hazard),
+            TileKind::Obstacle => ArenaTile::Obstacle,
+            TileKind::Prop(prop) => ArenaTile::Prop(prop),
+            TileKind::Outside => ArenaTile::Outside,
+        }
+    }
+}
+
+/// The overall shape of an arena.
+#[derive(Clone, Copy)]
+enum Outline {
+    /// Two crossing halls, meeting in the middle.
+    Cross,
+    Rectangle,
+    Rounded,
+    /// Two rooms either side of the middle, joined through narrow corridors.
+    Twin,
+}
+
+impl Outline {
+    const ALL: [Outline; 4] = [
+        Outline::Cross,
+        Outline::Rectangle,
+        Outline::Rounded,
+        Outline::Twin,
+    ];
+
+    /// Whether `offset` tiles from the middle of an arena `half_size` tiles across is inside it.
+    fn contains(&self, offset: Vec2, half_size: Vec2, corridor_half_width: f32) -> bool {
+        let offset = offset.abs();
+
+        match self {
+            Outline::Cross => {
+                let arm = half_size * 0.45;
+
+                (offset.x <= half_size.x && offset.y <= arm.y)
+                    || (offset.x <= arm.x && offset.y <= half_size.y)
+            }
+            Outline::Rectangle => offset.cmple(half_size).all(),
+            Outline::Rounded => (offset / half_size).length_squared() <= 1.0,
+            Outline::Twin => {
+                let room_half_width = half_size.x * 0.35;
+                let room_offset = half_size.x - room_half_width;
+
+                ((offset.x - room_offset).abs() <= room_half_width && offset.y <= half_size.y)
+                    || (offset.y <= corridor_half_width && offset.x <= half_size.x)
+            }
+        }
+    }
+}
+
+/// The layout of the current wave's arena, one tile per tile of the tilemap.
+#[derive(Resource)]
+pub struct Arena {
+    pub keep_heading: Vec2,
+    /// Where the keep starts, in world space.
+    pub keep_start: Vec2,
+    /// The bounds of the floor, in world space.
+    pub playable_area: Aabb2d,
+    /// Authored spawn points, in world space. If empty, enemies spawn from the wave's zones.
+    pub spawn_points: Vec<Vec2>,
+    /// Tiles drawn with a particular texture instead of an autotiled one.
+    textures: HashMap<UVec2, u32>,
+    tiles: Vec<ArenaTile>,
+}
+
+impl Arena {
+    /// The arena for a wave: mostly generated, with the authored `maps` mixed in.
+    pub fn for_wave(seed: u64, level: u32, maps: &[&ArenaMap]) -> Self {
+        let mut rng = WyRand::seed_from_u64(seed.wrapping_add(level as u64));
+
+        if (level + 1).is_multiple_of(AUTHORED_ARENA_INTERVAL)
+            && let Some(map) = maps.choose(&mut rng)
+        {
+            return Self::from_map(map);
+        }
+
+        Self::generate(seed, level)
+    }
+
+    /// An arena with no tiles of its own, for the open world. Its playable area follows the keep.
+    pub fn open_world() -> Self {
+        Self::empty()
+    }
+
+    fn empty() -> Self {
+        Self {
+            keep_heading: Vec2::Y,
+            keep_start: Vec2::ZERO,
+            playable_area: Aabb2d::new(Vec2::ZERO, Vec2::ZERO),
+            spawn_points: Vec::new(),
+            textures: HashMap::default(),
+            tiles: vec![ArenaTile::Outside; (AREA_SIZE.x * AREA_SIZE.y) as usize],
+        }
+    }
+
+    /// Lays out a hand-authored arena in the middle of the tilemap. Maps too big to fit are
+    /// turned away when they load.
+    fn from_map(map: &ArenaMap) -> Self {
+        let size = map.size();
+        let offset = AREA_SIZE.saturating_sub(size) / 2;
+        // Rows run down the screen, but tiles count up it.
+        let point =
+            |(column, row): (u32, u32)| offset + UVec2::new(column, size.y.saturating_sub(row + 1));
+
+        let mut arena = Self::empty();
+
+        for row in 0..size.y {
+            for column in 0..size.x {
+                let (point, Some(tile)) = (point((column, row)), map.tile(column, row)) else {
+                    continue;
+                };
+
+                arena.set(point, tile.kind.into());
+
+                if let Some(texture) = tile.texture {
+                    arena.textures.insert(point, texture);
+                }
+            }
+        }
+
+        arena.keep_heading = Vec2::from(map.keep.heading).normalize_or(Vec2::Y);
+        arena.keep_start = Self::tile_position(point(map.keep.position));
+        arena.playable_area = arena.floor_bounds();
+        arena.spawn_points = map
+            .spawn_points
+            .iter()
+            .map(|spawn_point| Self::tile_position(point(*spawn_point)))
+            .collect();
+
+        arena
+    }
+
+    /// Lays out a new arena. The same seed and level always give the same arena.
+    fn generate(seed: u64, level: u32) -> Self {
+        let mut rng = WyRand::seed_from_u64(seed.wrapping_add(level as u64));
+
+        let size = UVec2::new(
+            rng.gen_range(MIN_WIDTH..=MAX_WIDTH),
+            rng.gen_range(MIN_HEIGHT..=MAX_HEIGHT),
+        );
+        let outline = *Outline::ALL.choose(&mut rng).unwrap();
+        let corridor_half_width = rng.gen_range(CORRIDOR_HALF_WIDTH) as f32;
+        let half_size = size.as_vec2() / 2.0;
+
+        let mut arena = Self::empty();
+
+        for point in Self::points() {
+            let offset = Self::tile_position(point) / TILE_SIZE;
+
+            // The middle is always left open for the keep to loop around in.
+            if outline.contains(offset, half_size, corridor_half_width)
+                || offset.length() <= LOOP_SPACE_RADIUS
+            {
+                arena.set(point, ArenaTile::Floor);
+            }
+        }
+
+        arena.erode();
+
+        let obstacles = STARTING_OBSTACLES + level * OBSTACLES_PER_LEVEL;
+
+        for _ in 0..obstacles.min(MAX_OBSTACLES) {
+            arena.scatter_obstacle(&mut rng);
+        }
+
+        for _ in 0..(level * HAZARDS_PER_LEVEL).min(MAX_HAZARDS) {
+            arena.scatter_hazard(&mut rng);
+        }
+
+        for _ in 0..(STARTING_PROPS + level * PROPS_PER_LEVEL).min(MAX_PROPS) {
+            arena.scatter_prop(&mut rng);
+        }
+
+        arena.playable_area = arena.floor_bounds();
+
+        arena
+    }
+
+    fn index(point: UVec2) -> usize {
+        (point.y * AREA_SIZE.x + point.x) as usize
+    }
+
+    fn points() -> impl Iterator<Item = UVec2> {
+        (0..AREA_SIZE.y).flat_map(|y| (0..AREA_SIZE.x).map(move |x| UVec2::new(x, y)))
+    }
+
+    fn set(&mut self, point: UVec2, tile: ArenaTile) {
+        self.tiles[Self::index(point)] = tile;
+    }
+
+    /// The tile at `point`, treating anything off the tilemap as outside.
+    pub fn tile(&self, point: IVec2) -> ArenaTile {
+        if point.cmplt(IVec2::ZERO).any() || point.cmpge(AREA_SIZE.as_ivec2()).any() {
+            return ArenaTile::Outside;
+        }
+
+        self.tiles[Self::index(point.as_uvec2())]
+    }
+
+    /// The middle of the tile at `point`, in world space.
+    pub fn tile_position(point: UVec2) -> Vec2 {
+        (point.as_vec2() - (AREA_SIZE - 1).as_vec2() / 2.0) * TILE_SIZE
+    }
+
+    /// The tile that `position`, in world space, lies in.
+    fn tile_at(position: Vec2) -> IVec2 {
+        (position / TILE_SIZE + (AREA_SIZE - 1).as_vec2() / 2.0)
+            .round()
+            .as_ivec2()
+    }
+
+    /// Turns the tile `position` lies in back into floor, such as once the prop on it is smashed.
+    pub fn clear(&mut self, position: Vec2) {
+        let point = Self::tile_at(position);
+
+        if matches!(self.tile(point), ArenaTile::Prop(_)) {
+            self.set(point.as_uvec2(), ArenaTile::Floor);
+        }
+    }
+
+    /// The tiles whose middles lie within `bounds`, in world space.
+    pub fn tiles_within(bounds: &Aabb2d) -> IRect {
+        let offset = (AREA_SIZE - 1).as_vec2() / 2.0;
+
+        IRect {
+            min: (bounds.min / TILE_SIZE + offset).ceil().as_ivec2(),
+            max: (bounds.max / TILE_SIZE + offset).floor().as_ivec2(),
+        }
+    }
+
+    /// The bounds of `tiles`, in world space.
+    pub fn tile_bounds(tiles: IRect) -> Aabb2d {
+        let offset = (AREA_SIZE - 1).as_vec2() / 2.0;
+
+        Aabb2d {
+            min: (tiles.min.as_vec2() - offset - 0.5) * TILE_SIZE,
+            max: (tiles.max.as_vec2() - offset + 0.5) * TILE_SIZE,
+        }
+    }
+
+    /// Puts every tile within `from` but not `to` outside the walls, returning each one that
+    /// wasn't already. Their textures need picking again, along with their neighbours', so the
+    /// walls are drawn where the floor now ends.
+    pub fn close(&mut self, from: IRect, to: IRect) -> Vec<UVec2> {
+        let from = from.intersect(IRect::from_corners(IVec2::ZERO, AREA_SIZE.as_ivec2() - 1));
+        let closed = (from.min.y..=from.max.y)
+            .flat_map(|y| (from.min.x..=from.max.x).map(move |x| IVec2::new(x, y)))
+            .filter(|point| !to.contains(*point) && self.tile(*point) != ArenaTile::Outside)
+            .map(|point| point.as_uvec2())
+            .collect::<Vec<_>>();
+
+        for point in closed.iter() {
+            self.set(*point, ArenaTile::Outside);
+            self.textures.remove(point);
+        }
+
+        closed
+    }
+
+    /// Removes floor too narrow to have walls drawn on both sides, so every floor tile ends up
+    /// part of some 2×2 block of floor.
+    fn erode(&mut self) {
+        loop {
+            let narrow = Self::points()
+                .filter(|point| self.tiles[Self::index(*point)] == ArenaTile::Floor)
+                .filter(|point| {
+                    let point = point.as_ivec2();
+
+                    ![
+                        IVec2::new(-1, -1),
+                        IVec2::new(-1, 0),
+                        IVec2::new(0, -1),
+                        IVec2::ZERO,
+                    ]
+                    .iter()
+                    .any(|corner| {
+                        [IVec2::ZERO, IVec2::X, IVec2::Y, IVec2::ONE]
+                            .iter()
+                            .all(|offset| self.tile(point + *corner + *offset) == ArenaTile::Floor)
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            if narrow.is_empty() {
+                return;
+            }
+
+            for point in narrow {
+                self.set(point, ArenaTile::Outside);
+            }
+        }
+    }
+
+    fn floor_bounds(&self) -> Aabb2d {
+        let floor = Self::points()
+            .filter(|point| self.tiles[Self::index(*point)] != ArenaTile::Outside)
+            .map(Self::tile_position);
+        let (min, max) = floor.fold(
+            (Vec2::splat(f32::MAX), Vec2::splat(f32::MIN)),
+            |(min, max), position| (min.min(position), max.max(position)),
+        );
+
+        Aabb2d {
+            min: min - TILE_SIZE / 2.0,
+            max: max + TILE_SIZE / 2.0,
+        }
+    }
+
+    /// Every floor tile the keep can reach from the middle of the arena.
+    fn reachable_floor(&self) -> usize {
+        let start = Self::tile_at(Vec2::ZERO);
+        let mut visited = vec![false; self.tiles.len()];
+        let mut queue = VecDeque::from([start]);
+        let mut count = 0;
+
+        visited[Self::index(start.as_uvec2())] = true;
+
+        while let Some(point) = queue.pop_front() {
+            count += 1;
+
+            for step in [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y] {
+                let next = point + step;
+
+                if self.tile(next).is_solid() {
+                    continue;
+                }
+
+                let index = Self::index(next.as_uvec2());
+
+                if !visited[index] {
+                    visited[index] = true;
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        count
+    }
+
+    /// Drops a rock of one or four tiles somewhere clear of the walls, other rocks and the keep's
+    /// loop space, as long as it doesn't cut any of the floor off.
+    fn scatter_obstacle(&mut self, rng: &mut impl Rng) {
+        let floor_before = self.reachable_floor();
+
+        for _ in 0..OBSTACLE_ATTEMPTS {
+            let size = if rng.gen_bool(0.5) { 1 } else { 2 };
+            let min = IVec2::new(
+                rng.gen_range(0..AREA_SIZE.x as i32 - size),
+                rng.gen_range(0..AREA_SIZE.y as i32 - size),
+            );
+            let rock = (0..size)
+                .flat_map(|x| (0..size).map(move |y| min + IVec2::new(x, y)))
+                .collect::<Vec<_>>();
+
+            let clear = rock.iter().all(|point| {
+                Self::tile_position(point.as_uvec2()).length() / TILE_SIZE > LOOP_SPACE_RADIUS + 1.0
+                    && (-1..=1).all(|x| {
+                        (-1..=1).all(|y| self.tile(*point + IVec2::new(x, y)) == ArenaTile::Floor)
+                    })
+            });
+
+            if !clear {
+                continue;
+            }
+
+            for point in rock.iter() {
+                self.set(point.as_uvec2(), ArenaTile::Obstacle);
+            }
+
+            if self.reachable_floor() + rock.len() == floor_before {
+                return;
+            }
+
+            for point in rock.iter() {
+                self.set(point.as_uvec2(), ArenaTile::Floor);
+            }
+        }
+    }
+
+    /// Lays a patch of a random hazard somewhere clear of the walls, rocks, other hazards and the
+    /// keep's loop space.
+    fn scatter_hazard(&mut self, rng: &mut impl Rng) {
+        let hazard = *Hazard::ALL.choose(rng).unwrap();
+
+        for _ in 0..HAZARD_ATTEMPTS {
+            let size = IVec2::new(
+                rng.gen_range(HAZARD_PATCH_SIZE),
+                rng.gen_range(HAZARD_PATCH_SIZE),
+            );
+            let min = IVec2::new(
+                rng.gen_range(0..AREA_SIZE.x as i32 - size.x),
+                rng.gen_range(0..AREA_SIZE.y as i32 - size.y),
+            );
+            let patch = (0..size.x)
+                .flat_map(|x| (0..size.y).map(move |y| min + IVec2::new(x, y)))
+                .collect::<Vec<_>>();
+
+            let clear = patch.iter().all(|point| {
+                Self::tile_position(point.as_uvec2()).length() / TILE_SIZE > LOOP_SPACE_RADIUS
+                    && (-1..=1).all(|x| {
+                        (-1..=1).all(|y| self.tile(*point + IVec2::new(x, y)) == ArenaTile::Floor)
+                    })
+            });
+
+            if !clear {
+                continue;
+            }
+
+            for point in patch {
+                self.set(point.as_uvec2(), ArenaTile::Hazard(hazard));
+            }
+
+            return;
+        }
+    }
+
+    /// Stands a crate or tree, or a short run of fence, somewhere clear of the walls, rocks,
+    /// hazards, other props and the keep's loop space. Props can be smashed, so unlike rocks they
+    /// are free to block the way.
+    fn scatter_prop(&mut self, rng: &mut impl Rng) {
+        let prop = *Prop::ALL.choose(rng).unwrap();
+
+        for _ in 0..PROP_ATTEMPTS {
+            let size = match prop {
+                Prop::Fence if rng.gen_bool(0.5) => IVec2::new(rng.gen_range(FENCE_LENGTH), 1),
+                Prop::Fence => IVec2::new(1, rng.gen_range(FENCE_LENGTH)),
+                Prop::Crate | Prop::Tree => IVec2::ONE,
+            };
+            let min = IVec2::new(
+                rng.gen_range(0..AREA_SIZE.x as i32 - size.x),
+                rng.gen_range(0..AREA_SIZE.y as i32 - size.y),
+            );
+            let run = (0..size.x)
+                .flat_map(|x| (0..size.y).map(move |y| min + IVec2::new(x, y)))
+                .collect::<Vec<_>>();
+
+            let clear = run.iter().all(|point| {
+                Self::tile_position(point.as_uvec2()).length() / TILE_SIZE > LOOP_SPACE_RADIUS + 1.0
+                    && (-1..=1).all(|x| {
+                        (-1..=1).all(|y| self.tile(*point + IVec2::new(x, y)) == ArenaTile::Floor)
+                    })
+            });
+
+            if !clear {
+                continue;
+            }
+
+            for point in run {
+                self.set(point.as_uvec2(), ArenaTile::Prop(prop));
+            }
+
+            return;
+        }
+    }
+
+    /// Picks the tile from `terrain.png` that fits `point`, with `rules` drawing walls wherever
+    /// the floor meets the outside, unless the arena says otherwise. Obstacles, hazards and props
+    /// sit on the floor, so they don't get walls.
+    pub fn texture_index(
+        &self,
+        point: UVec2,
+        rules: &AutotileRules,
+        rng: &mut impl Rng,
+    ) -> TileTextureIndex {
+        if let Some(texture) = self.textures.get(&point) {
+            return TileTextureIndex(*texture);
+        }
+
+        let point = point.as_ivec2();
+
+        if let Some(texture) = self.tile(point).texture() {
+            return texture;
+        }
+
+        TileTextureIndex(rules.texture_index(
+            point,
+            |point| self.tile(point) == ArenaTile::Outside,
+            rng,
+        ))
+    }
+}
@@ -0,0 +1,207 @@
+use bevy::{math::bounding::*, prelude::*, utils::HashSet};
+use bevy_ecs_tilemap::prelude::*;
+use bevy_prng::WyRand;
+use bevy_rand::prelude::*;
+use rand::{Rng, seq::SliceRandom};
+
+use crate::asset_handles::AssetHandles;
+use crate::autotile::AutotileRules;
+use crate::game::game_sets::PausableSet;
+use crate::health::Health;
+
+use super::WaveTilemap;
+use super::announcement::{Announcement, AnnouncementStyle};
+use super::arena::{AREA_SIZE, Arena, TILE_SIZE};
+use super::enemy::Enemy;
+use super::hazard::HazardTile;
+use super::player::Player;
+use super::prop::PropTile;
+use super::tile_collision::SolidTile;
+use super::wave_controller::WaveController;
+use super::wave_sets::WaveRunningSet;
+use super::wave_state::WaveState;
+
+/// A shrinking arena stops closing in once it is this many tiles from its middle, which leaves
+/// the keep room to loop around.
+const MIN_HALF_SIZE: f32 = 8.0;
+/// How many tiles a sliding arena's trailing wall advances over the wave.
+const SLIDE_DISTANCE: f32 = 12.0;
+
+#[derive(Clone, Copy)]
+enum ArenaShiftKind {
+    /// Every wall closes in on the middle.
+    Shrink,
+    /// The wall opposite this direction sweeps across the arena.
+    Slide(Vec2),
+}
+
+/// Walls that close in on the arena as the wave timer runs down.
+#[derive(Resource)]
+pub struct ArenaShift {
+    kind: ArenaShiftKind,
+    /// The tiles not walled off yet.
+    open: IRect,
+    /// The playable area before anything moved.
+    origin: Aabb2d,
+}
+
+impl ArenaShift {
+    pub fn roll(rng: &mut impl Rng, arena: &Arena) -> Self {
+        let kind = if rng.gen_bool(0.5) {
+            ArenaShiftKind::Shrink
+        } else {
+            ArenaShiftKind::Slide(
+                *[Vec2::X, Vec2::NEG_X, Vec2::Y, Vec2::NEG_Y]
+                    .choose(rng)
+                    .unwrap(),
+            )
+        };
+
+        Self {
+            kind,
+            open: Arena::tiles_within(&arena.playable_area),
+            origin: arena.playable_area,
+        }
+    }
+
+    /// The playable area once `progress`, from 0 to 1, of the wave has passed.
+    fn bounds(&self, progress: f32) -> Aabb2d {
+        match self.kind {
+            ArenaShiftKind::Shrink => {
+                let half_size = self.origin.half_size();
+                let min_half_size = half_size.min(Vec2::splat(MIN_HALF_SIZE * TILE_SIZE));
+
+                Aabb2d::new(
+                    self.origin.center(),
+                    half_size.lerp(min_half_size, progress),
+                )
+            }
+            ArenaShiftKind::Slide(direction) => {
+                let offset = direction * SLIDE_DISTANCE * TILE_SIZE * progress;
+
+                Aabb2d {
+                    min: (self.origin.min + offset).max(self.origin.min),
+                    max: (self.origin.max + offset).min(self.origin.max),
+                }
+            }
+        }
+    }
+}
+
+fn announce_arena_shift(mut announcement_events: EventWriter<Announcement>) {
+    announcement_events.send(
+        Announcement::new("The walls are closing in!").with_style(AnnouncementStyle::Warning),
+    );
+}
+
+/// Walls off the tiles the moving boundary has passed, once it reaches another row or column of
+/// them, and pushes the keep and enemies back out of the way.
+fn shift_arena(
+    mut arena_shift: ResMut<ArenaShift>,
+    mut arena: ResMut<Arena>,
+    asset_handles: Res<AssetHandles>,
+    mut commands: Commands,
+    mut enemy_query: Query<(&Enemy, &mut Transform), Without<Player>>,
+    mut global_rng: GlobalEntropy<WyRand>,
+    mut player_query: Query<(&Player, &mut Transform), Without<Enemy>>,
+    mut texture_query: Query<&mut TileTextureIndex>,
+    tilemap_query: Query<&TileStorage, With<WaveTilemap>>,
+    wave_controller: Res<WaveController>,
+) {
+    let bounds = arena_shift.bounds(wave_controller.wave_timer.fraction());
+
+    arena.playable_area = bounds;
+
+    let open = Arena::tiles_within(&bounds);
+
+    if open == arena_shift.open {
+        return;
+    }
+
+    let Ok(tile_storage) = tilemap_query.get_single() else {
+        return;
+    };
+
+    let closed = arena.close(arena_shift.open, open);
+
+    arena_shift.open = open;
+
+    let open_bounds = Arena::tile_bounds(open);
+    let push_inside = |volume: BoundingCircle| {
+        let radius = volume.radius();
+
+        volume
+            .center
+            .max(open_bounds.min + radius)
+            .min(open_bounds.max - radius)
+            - volume.center
+    };
+
+    for (player, mut transform) in player_query.iter_mut() {
+        let push = push_inside(player.volume(&transform));
+
+        transform.translation += push.extend(0.0);
+    }
+
+    for (enemy, mut transform) in enemy_query.iter_mut() {
+        let push = push_inside(enemy.volume(&transform));
+
+        transform.translation += push.extend(0.0);
+    }
+
+    // The walls are drawn by the tiles either side of where the floor ends.
+    let mut redrawn = HashSet::new();
+
+    for point in closed {
+        if let Some(tile_entity) = tile_storage.get(&TilePos::from(point)) {
+            commands
+                .entity(tile_entity)
+                .remove::<(HazardTile, Health, PropTile)>()
+                .insert(SolidTile);
+        }
+
+        for x in -1..=1 {
+            for y in -1..=1 {
+                let neighbour = point.as_ivec2() + IVec2::new(x, y);
+
+                if neighbour.cmpge(IVec2::ZERO).all() && neighbour.cmplt(AREA_SIZE.as_ivec2()).all()
+                {
+                    redrawn.insert(neighbour.as_uvec2());
+                }
+            }
+        }
+    }
+
+    let default_rules = AutotileRules::default();
+    let autotile_rules = asset_handles
+        .autotile_rules("terrain")
+        .unwrap_or(&default_rules);
+
+    for point in redrawn {
+        let Some(tile_entity) = tile_storage.get(&TilePos::from(point)) else {
+            continue;
+        };
+
+        if let Ok(mut texture_index) = texture_query.get_mut(tile_entity) {
+            *texture_index = arena.texture_index(point, autotile_rules, global_rng.as_mut());
+        }
+    }
+}
+
+pub struct ArenaShiftPlugin;
+
+impl Plugin for ArenaShiftPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(WaveState::Running),
+            announce_arena_shift.run_if(resource_exists::<ArenaShift>),
+        );
+        app.add_systems(
+            Update,
+            shift_arena
+                .run_if(resource_exists::<ArenaShift>)
+                .in_set(PausableSet)
+                .in_set(WaveRunningSet),
+        );
+    }
+}
@@ -0,0 +1,222 @@
+use bevy::prelude::*;
+use rand::{Rng, seq::SliceRandom};
+
+use crate::asset_handles::AssetHandles;
+use crate::colors::GOLD;
+use crate::game::game_sets::PausableSet;
+use crate::health::{DamageDealt, DamageEvent, DamageType, Died, Health, HealthState};
+use crate::widgets;
+
+use super::enemy::{Enemy, initialize_enemy};
+use super::player::Player;
+use super::wave_sets::WaveRunningSet;
+
+const AFFIXES: [Affix; 6] = [
+    Affix::Armored,
+    Affix::Explosive,
+    Affix::Fast,
+    Affix::Regenerating,
+    Affix::Shielded,
+    Affix::Vampiric,
+];
+const ARMORED_ARMOR: u32 = 1;
+const ARMORED_RESISTANCE: f32 = 0.25;
+const ELITE_HEALTH_MULTIPLIER: u32 = 2;
+const ELITE_SCALE: f32 = 1.15;
+const EXPLOSION_DAMAGE: u32 = 3;
+const EXPLOSION_RADIUS: f32 = 48.0;
+const FAST_SPEED_MULTIPLIER: f32 = 1.5;
+const LABEL_FONT_SIZE: f32 = 6.0;
+const LABEL_OFFSET: f32 = 14.0;
+const REGENERATION_RATE: f32 = 2.0;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Affix {
+    /// Shrugs off part of every hit, and some of each arrow.
+    Armored,
+    /// Blows up when it dies, hurting the keep if it is close.
+    Explosive,
+    Fast,
+    /// Slowly heals.
+    Regenerating,
+    /// Has a shield that soaks up damage before its health.
+    Shielded,
+    /// Heals by however much it hurts the keep.
+    Vampiric,
+}
+
+impl Affix {
+    fn name(&self) -> &'static str {
+        match self {
+            Affix::Armored => "Armored",
+            Affix::Explosive => "Explosive",
+            Affix::Fast => "Fast",
+            Affix::Regenerating => "Regenerating",
+            Affix::Shielded => "Shielded",
+            Affix::Vampiric => "Vampiric",
+        }
+    }
+}
+
+/// Marks an enemy that rolled one or more affixes.
+#[derive(Component)]
+#[require(Enemy)]
+pub struct Elite {
+    pub affixes: Vec<Affix>,
+    regeneration_timer: Timer,
+}
+
+impl Elite {
+    /// Rolls up to `max_affixes` different affixes.
+    pub fn roll(rng: &mut impl Rng, max_affixes: usize) -> Self {
+        let count = rng.gen_range(1..=max_affixes.clamp(1, AFFIXES.len()));
+
+        Self {
+            affixes: AFFIXES.choose_multiple(rng, count).copied().collect(),
+            regeneration_timer: Timer::from_seconds(REGENERATION_RATE, TimerMode::Repeating),
+        }
+    }
+
+    pub fn has(&self, affix: Affix) -> bool {
+        self.affixes.contains(&affix)
+    }
+}
+
+fn elite_explosion(
+    mut damage_events: EventWriter<DamageEvent>,
+    mut died_events: EventReader<Died>,
+    elite_query: Query<(&Elite, &Transform)>,
+    player_query: Query<(Entity, &Transform), With<Player>>,
+) {
+    let Ok((player_entity, player_transform)) = player_query.get_single() else {
+        return;
+    };
+
+    for event in died_events.read() {
+        let Ok((elite, elite_transform)) = elite_query.get(event.entity) else {
+            continue;
+        };
+
+        if !elite.has(Affix::Explosive) {
+            continue;
+        }
+
+        let distance = elite_transform
+            .translation
+            .xy()
+            .distance(player_transform.translation.xy());
+
+        if distance <= EXPLOSION_RADIUS {
+            damage_events.send(DamageEvent {
+                amount: EXPLOSION_DAMAGE,
+                damage_type: DamageType::Fire,
+                source: Some(event.entity),
+                target: player_entity,
+            });
+        }
+    }
+}
+
+fn elite_regeneration(mut query: Query<(&mut Elite, &mut Health)>, time: Res<Time>) {
+    for (mut elite, mut health) in query.iter_mut() {
+        if !elite.has(Affix::Regenerating) || health.state == HealthState::Dead {
+            continue;
+        }
+
+        elite.regeneration_timer.tick(time.delta());
+
+        if elite.regeneration_timer.just_finished() && health.current < health.max {
+            health.current += 1;
+        }
+    }
+}
+
+/// Only the health the keep actually lost counts, so hits soaked up by its armour heal nothing.
+fn elite_vampirism(
+    mut damage_dealt_events: EventReader<DamageDealt>,
+    mut elite_query: Query<(&Elite, &mut Health)>,
+    player_query: Query<(), With<Player>>,
+) {
+    for event in damage_dealt_events.read() {
+        let Some(source) = event.source else {
+            continue;
+        };
+
+        if !player_query.contains(event.target) {
+            continue;
+        }
+
+        let Ok((elite, mut health)) = elite_query.get_mut(source) else {
+            continue;
+        };
+
+        if elite.has(Affix::Vampiric) && health.state != HealthState::Dead {
+            health.current = (health.current + event.amount).min(health.max);
+        }
+    }
+}
+
+fn initialize_elite(
+    asset_handles: Res<AssetHandles>,
+    mut commands: Commands,
+    mut query: Query<(Entity, &Elite, &mut Enemy, &mut Health, &mut Transform), Added<Elite>>,
+) {
+    for (entity, elite, mut enemy, mut health, mut transform) in query.iter_mut() {
+        health.max *= ELITE_HEALTH_MULTIPLIER;
+        health.current = health.max;
+        transform.scale *= ELITE_SCALE;
+
+        for affix in elite.affixes.iter() {
+            match affix {
+                Affix::Armored => {
+                    health.armor += ARMORED_ARMOR;
+                    health
+                        .resistances
+                        .insert(DamageType::Pierce, ARMORED_RESISTANCE);
+                }
+                Affix::Fast => {
+                    enemy.speed *= FAST_SPEED_MULTIPLIER;
+                }
+                Affix::Shielded => {
+                    health.shield = health.max;
+                }
+                Affix::Explosive | Affix::Regenerating | Affix::Vampiric => {}
+            }
+        }
+
+        let name = elite
+            .affixes
+            .iter()
+            .map(Affix::name)
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        commands.entity(entity).with_child((
+            Text2d::new(name),
+            TextColor(GOLD),
+            TextFont {
+                font_size: LABEL_FONT_SIZE,
+                ..widgets::text_font(&asset_handles)
+            },
+            Transform::from_xyz(0.0, LABEL_OFFSET, 1.0),
+        ));
+    }
+}
+
+pub struct ElitePlugin;
+
+impl Plugin for ElitePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                elite_explosion,
+                elite_regeneration,
+                elite_vampirism,
+                initialize_elite.after(initialize_enemy),
+            )
+                .in_set(PausableSet)
+                .in_set(WaveRunningSet),
+        );
+    }
+}
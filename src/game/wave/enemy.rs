@@ -1,18 +1,45 @@
 use bevy::{math::bounding::*, prelude::*};
 
+use crate::animation_controller::{AnimationController, AnimationParameters};
 use crate::{asset_handles::AssetHandles, game::game_sets::PausableSet};
-use crate::health::Health;
-use crate::simple_animations::SimpleAnimation;
+use crate::health::{DamageEvent, DamageType, Died, Health};
+use crate::simple_animations::{AnimationFinished, SimpleAnimation};
 
+use super::hazard::Footing;
+use super::hunter::Hunter;
+use super::knockback::{Knockback, KnockbackEvent, knockback_movement};
 use super::player::{Player, PlayerState};
+use super::status_effect::StatusEffects;
+use super::steering::{SpatialGrid, SteeringWeights, steering_direction, update_spatial_grid};
+use super::tile_collision::TileCollision;
 use super::wave_sets::WaveRunningSet;
 use super::wave_state::WaveState;
+use super::Arena;
 
+const BRUTE_DAMAGE: u32 = 4;
+const BRUTE_HEALTH: u32 = 15;
+const BRUTE_KNOCKBACK_RESISTANCE: f32 = 0.8;
+const BRUTE_LOOT_VALUE: u32 = 3;
+const BRUTE_SCALE: f32 = 1.5;
+const BRUTE_SPEED: f32 = 70.0;
 const NORMAL_DAMAGE: u32 = 2;
+const NORMAL_HEALTH: u32 = 5;
+const NORMAL_LOOT_VALUE: u32 = 1;
 const NORMAL_SIZE: f32 = 8.0;
 const DEFAULT_SPEED: f32 = 120.0;
-const DEATH_RATE: f32 = 1.0;
-const SPAWN_RATE: f32 = 1.0;
+const SABOTEUR_HEALTH: u32 = 6;
+const SABOTEUR_LOOT_VALUE: u32 = 2;
+const SABOTEUR_SCALE: f32 = 0.75;
+const SABOTEUR_SPEED: f32 = 140.0;
+const SNIPER_DAMAGE: u32 = 1;
+const SNIPER_HEALTH: u32 = 4;
+const SNIPER_LOOT_VALUE: u32 = 2;
+/// How far from the keep snipers hold while they take their shots.
+const SNIPER_STANDOFF: f32 = 112.0;
+const SNIPER_SCALE: f32 = 0.9;
+const SNIPER_SPEED: f32 = 90.0;
+/// How often the keep can ram the same enemy.
+const RAM_COOLDOWN: f32 = 0.5;
 
 #[derive(Default, Eq, PartialEq)]
 pub enum EnemyState {
@@ -22,50 +49,163 @@ pub enum EnemyState {
     Spawning,
 }
 
+impl EnemyState {
+    /// The state name the enemy's animation controller reacts to.
+    fn animation_state(&self) -> &'static str {
+        match self {
+            EnemyState::Active => "active",
+            EnemyState::Dead => "dead",
+            EnemyState::Spawning => "spawning",
+        }
+    }
+}
+
 #[derive(Default, Eq, PartialEq)]
 pub enum EnemyType {
+    /// A slow, heavy enemy that shrugs off most knockback.
+    Brute,
     #[default]
     Normal,
+    /// Latches onto a defender and wrecks it, leaving the keep alone.
+    Saboteur,
+    /// Keeps its distance and shoots the defenders.
+    Sniper,
+}
+
+impl EnemyType {
+    /// The key of the animation controller in the manifest.
+    fn animation_controller(&self) -> &'static str {
+        match self {
+            EnemyType::Brute | EnemyType::Normal | EnemyType::Saboteur | EnemyType::Sniper => {
+                "enemy"
+            }
+        }
+    }
+
+    fn health(&self) -> u32 {
+        match self {
+            EnemyType::Brute => BRUTE_HEALTH,
+            EnemyType::Normal => NORMAL_HEALTH,
+            EnemyType::Saboteur => SABOTEUR_HEALTH,
+            EnemyType::Sniper => SNIPER_HEALTH,
+        }
+    }
+
+    /// How much gold the enemy drops.
+    pub fn loot_value(&self) -> u32 {
+        match self {
+            EnemyType::Brute => BRUTE_LOOT_VALUE,
+            EnemyType::Normal => NORMAL_LOOT_VALUE,
+            EnemyType::Saboteur => SABOTEUR_LOOT_VALUE,
+            EnemyType::Sniper => SNIPER_LOOT_VALUE,
+        }
+    }
+
+    fn knockback_resistance(&self) -> f32 {
+        match self {
+            EnemyType::Brute => BRUTE_KNOCKBACK_RESISTANCE,
+            EnemyType::Normal | EnemyType::Saboteur | EnemyType::Sniper => 0.0,
+        }
+    }
+
+    fn steering(&self) -> SteeringWeights {
+        match self {
+            // Brutes barge straight in.
+            EnemyType::Brute => SteeringWeights {
+                avoidance: 1.0,
+                cohesion: 0.0,
+                flank: 0.0,
+                neighbour_radius: 32.0,
+                separation: 0.8,
+                standoff: 0.0,
+            },
+            EnemyType::Normal => SteeringWeights {
+                avoidance: 1.0,
+                cohesion: 0.3,
+                flank: 1.0,
+                neighbour_radius: 24.0,
+                separation: 1.5,
+                standoff: 0.0,
+            },
+            // Saboteurs slip around the crowd on their own.
+            EnemyType::Saboteur => SteeringWeights {
+                avoidance: 1.0,
+                cohesion: 0.0,
+                flank: 0.5,
+                neighbour_radius: 24.0,
+                separation: 1.0,
+                standoff: 0.0,
+            },
+            EnemyType::Sniper => SteeringWeights {
+                avoidance: 1.0,
+                cohesion: 0.0,
+                flank: 1.0,
+                neighbour_radius: 32.0,
+                separation: 1.5,
+                standoff: SNIPER_STANDOFF,
+            },
+        }
+    }
+
+    fn scale(&self) -> f32 {
+        match self {
+            EnemyType::Brute => BRUTE_SCALE,
+            EnemyType::Normal => 1.0,
+            EnemyType::Saboteur => SABOTEUR_SCALE,
+            EnemyType::Sniper => SNIPER_SCALE,
+        }
+    }
 }
 
 #[derive(Component)]
-#[require(Health, SimpleAnimation(|| SimpleAnimation {
-        current_frame_index: 0,
-        frames: vec![3, 4],
-        ..default()
-}), Sprite, Transform, Visibility)]
+#[require(AnimationParameters, Footing, Health, Knockback, Sprite, StatusEffects, Transform, Visibility)]
 pub struct Enemy {
+    /// Stops the keep from ramming and shoving the same enemy every frame.
+    pub contact_timer: Timer,
+    /// Contact damage dealt to the keep. Enemies with none just get in its way.
     pub damage: u32,
-    pub death_timer: Timer,
     pub direction: Vec2,
     pub enemy_type: EnemyType,
     pub enemy_state: EnemyState,
-    pub spawn_timer: Timer,
+    /// The angle this enemy swings around the keep by, so a crowd attacks from all sides.
+    pub flank_angle: f32,
     pub speed: f32,
 }
 
 impl Enemy {
+    pub fn new(enemy_type: EnemyType) -> Self {
+        let (damage, speed) = match enemy_type {
+            EnemyType::Brute => (BRUTE_DAMAGE, BRUTE_SPEED),
+            EnemyType::Normal => (NORMAL_DAMAGE, DEFAULT_SPEED),
+            EnemyType::Saboteur => (0, SABOTEUR_SPEED),
+            EnemyType::Sniper => (SNIPER_DAMAGE, SNIPER_SPEED),
+        };
+
+        let mut contact_timer = Timer::from_seconds(RAM_COOLDOWN, TimerMode::Once);
+
+        // Start ready to be rammed.
+        contact_timer.tick(contact_timer.duration());
+
+        Self {
+            contact_timer,
+            damage,
+            enemy_type,
+            enemy_state: EnemyState::default(),
+            direction: Vec2::ZERO,
+            flank_angle: 0.0,
+            speed,
+        }
+    }
+
+    /// Follows the transform's scale, which covers both the enemy type and elites.
     pub fn volume(&self, transform: &Transform) -> BoundingCircle {
-        BoundingCircle::new(
-            transform.translation.xy(),
-            match self.enemy_type {
-                EnemyType::Normal => NORMAL_SIZE,
-            },
-        )
+        BoundingCircle::new(transform.translation.xy(), NORMAL_SIZE * transform.scale.x)
     }
 }
 
 impl Default for Enemy {
     fn default() -> Self {
-        Self {
-            damage: NORMAL_DAMAGE,
-            death_timer: Timer::from_seconds(DEATH_RATE, TimerMode::Once),
-            enemy_type: EnemyType::default(),
-            enemy_state: EnemyState::default(),
-            direction: Vec2::ZERO,
-            spawn_timer: Timer::from_seconds(SPAWN_RATE, TimerMode::Once),
-            speed: DEFAULT_SPEED,
-        }
+        Self::new(EnemyType::default())
     }
 }
 
@@ -75,127 +215,206 @@ fn destroy_enemies(mut commands: Commands, query: Query<Entity, With<Enemy>>) {
     }
 }
 
-fn enemy_behavior(
+/// Spawning and dying both last as long as their animation, whichever clip the controller picks.
+fn enemy_animation_finished(
     mut commands: Commands,
-    mut enemy_query: Query<(
-        &mut Enemy,
-        Entity,
-        &mut SimpleAnimation,
-        &mut Sprite,
-        &Transform,
-    )>,
-    player_query: Query<(&Player, &Transform)>,
-    time: Res<Time>,
+    mut enemy_query: Query<(&mut Enemy, &SimpleAnimation)>,
+    mut finished_events: EventReader<AnimationFinished>,
 ) {
-    let Ok((player, player_transform)) = player_query.get_single() else {
-        return;
-    };
+    for event in finished_events.read() {
+        let Ok((mut enemy, animation)) = enemy_query.get_mut(event.entity) else {
+            continue;
+        };
+
+        // Ignore clips the controller has already moved on from.
+        if event.clip != animation.clip.name {
+            continue;
+        }
 
-    for (mut enemy, enemy_entity, mut enemy_animation, mut enemy_sprite, enemy_transform) in
-        enemy_query.iter_mut()
-    {
         match enemy.enemy_state {
             EnemyState::Spawning => {
-                enemy.direction = Vec2::ZERO;
-                enemy.spawn_timer.tick(time.delta());
-
-                if (enemy.spawn_timer.just_finished()) {
-                    enemy.enemy_state = EnemyState::Active;
-                    enemy_animation.animation_timer.reset();
-                    enemy_animation.current_frame_index = 0;
-                    enemy_animation.frames = vec![0, 1];
-                }
+                enemy.enemy_state = EnemyState::Active;
             }
-            EnemyState::Active => {
-                match enemy.enemy_type {
-                    EnemyType::Normal => {
-                        enemy.direction =
-                            (player_transform.translation - enemy_transform.translation).truncate();
+            EnemyState::Dead => {
+                commands.entity(event.entity).despawn_recursive();
+            }
+            _ => {}
+        }
+    }
+}
 
-                        enemy.direction = enemy.direction.normalize();
+fn enemy_animation_parameters(mut query: Query<(&Enemy, &mut AnimationParameters)>) {
+    for (enemy, mut parameters) in query.iter_mut() {
+        parameters.direction = enemy.direction;
+        parameters.state = enemy.enemy_state.animation_state();
+    }
+}
 
-                        enemy_sprite.flip_x = enemy.direction.x < 0.0;
-                    }
-                };
-            }
-            EnemyState::Dead => {
-                enemy.direction = Vec2::ZERO;
-                enemy.death_timer.tick(time.delta());
+fn enemy_behavior(
+    arena: Res<Arena>,
+    mut enemy_query: Query<(Entity, &mut Enemy, &StatusEffects, &Transform)>,
+    grid: Res<SpatialGrid>,
+    player_query: Query<&Transform, With<Player>>,
+    tiles: TileCollision,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+
+    for (enemy_entity, mut enemy, status_effects, enemy_transform) in enemy_query.iter_mut() {
+        if status_effects.is_stunned() {
+            continue;
+        }
 
-                if (enemy.death_timer.just_finished()) {
-                    commands.entity(enemy_entity).despawn();
-                }
+        match enemy.enemy_state {
+            EnemyState::Spawning | EnemyState::Dead => {
+                enemy.direction = Vec2::ZERO;
+            }
+            EnemyState::Active => {
+                enemy.direction = steering_direction(
+                    enemy_entity,
+                    enemy_transform.translation.xy(),
+                    player_transform.translation.xy(),
+                    enemy.flank_angle,
+                    enemy.enemy_type.steering(),
+                    &grid,
+                    &arena.playable_area,
+                    |position| tiles.is_solid(position),
+                );
             }
         }
     }
 }
 
-fn enemy_death(mut query: Query<(&mut Enemy, &Health, &mut SimpleAnimation)>) {
-    for (mut enemy, health, mut simple_animation) in query.iter_mut() {
-        if health.current != 0 || enemy.enemy_state == EnemyState::Dead {
+fn enemy_death(
+    mut died_events: EventReader<Died>,
+    mut query: Query<(&mut Enemy, &mut StatusEffects)>,
+) {
+    for event in died_events.read() {
+        let Ok((mut enemy, mut status_effects)) = query.get_mut(event.entity) else {
             continue;
-        }
+        };
+
+        debug!("Enemy {} killed by {:?}", event.entity, event.killer);
 
-        
         enemy.enemy_state = EnemyState::Dead;
-        simple_animation.animation_timer.reset();
-        simple_animation.current_frame_index = 0;
-        simple_animation.frames = vec![2];
+        status_effects.clear();
     }
 }
 
-fn enemy_movement(mut query: Query<(&Enemy, &mut Transform)>, time: Res<Time>) {
-    for (enemy, mut transform) in query.iter_mut() {
-        if enemy.enemy_state != EnemyState::Active {
+pub fn enemy_movement(
+    mut query: Query<(&Enemy, &Footing, &StatusEffects, &mut Transform)>,
+    time: Res<Time>,
+) {
+    for (enemy, footing, status_effects, mut transform) in query.iter_mut() {
+        if enemy.enemy_state != EnemyState::Active || status_effects.is_stunned() {
             continue;
         }
 
-        let translation = enemy.direction * enemy.speed * time.delta_secs();
+        let speed = enemy.speed * status_effects.speed_multiplier() * footing.speed_multiplier();
+        let translation = enemy.direction * speed * time.delta_secs();
 
         transform.translation += translation.extend(0.0);
     }
 }
 
-fn initialize_enemy(
+/// Enemies slide along obstacles rather than walking through them.
+fn enemy_obstacles(mut query: Query<(&Enemy, &mut Transform)>, tiles: TileCollision) {
+    for (enemy, mut transform) in query.iter_mut() {
+        let push = tiles.push_out(&enemy.volume(&transform));
+
+        if push != Vec2::ZERO {
+            transform.translation += push.extend(0.0);
+        }
+    }
+}
+
+pub fn initialize_enemy(
     asset_handles: Res<AssetHandles>,
-    mut query: Query<(&mut Health, &mut Sprite), Added<Enemy>>,
+    mut commands: Commands,
+    mut query: Query<
+        (Entity, &Enemy, &mut Health, &mut Knockback, &mut Sprite, &mut Transform),
+        Added<Enemy>,
+    >,
 ) {
-    for (mut health, mut sprite) in query.iter_mut() {
-        health.max = 5;
+    for (enemy_entity, enemy, mut health, mut knockback, mut sprite, mut transform) in
+        query.iter_mut()
+    {
+        health.max = enemy.enemy_type.health();
         health.current = health.max;
+        knockback.resistance = enemy.enemy_type.knockback_resistance();
+        transform.scale = Vec3::splat(enemy.enemy_type.scale());
 
-        sprite.image = asset_handles.image_map.get("enemy").unwrap().clone();
-        sprite.texture_atlas = Some(TextureAtlas {
-            index: 3,
-            layout: asset_handles
-                .texture_atlas_layout_map
-                .get("enemy")
-                .unwrap()
-                .clone(),
-        });
+        commands
+            .entity(enemy_entity)
+            .insert(AnimationController::new(enemy.enemy_type.animation_controller()));
+        sprite.image = asset_handles.image("enemy");
+        sprite.texture_atlas = Some(asset_handles.texture_atlas("enemy", 3));
     }
 }
 
+/// Enemies touching the keep hurt it, get shoved out of the way and are rammed for trample
+/// damage. Saboteurs riding on the keep aren't touching it, they're on board.
 fn player_hit(
-    enemy_query: Query<(&Enemy, &Transform)>,
-    mut player_query: Query<(&mut Health, &mut Player, &Transform)>,
+    mut damage_events: EventWriter<DamageEvent>,
+    mut enemy_query: Query<(Entity, &mut Enemy, Option<&Hunter>, &Transform)>,
+    mut knockback_events: EventWriter<KnockbackEvent>,
+    mut player_query: Query<(Entity, &mut Player, &Transform)>,
+    time: Res<Time>,
 ) {
-    let Ok((mut health, mut player, player_transform)) = player_query.get_single_mut() else {
+    let Ok((player_entity, mut player, player_transform)) = player_query.get_single_mut() else {
         return;
     };
 
-    if player.player_state == PlayerState::Invincible {
+    if player.player_state == PlayerState::Dead {
         return;
     }
 
     let player_volume = player.volume(player_transform);
 
-    for (enemy, enemy_transform) in enemy_query.iter() {
-        if !enemy.volume(enemy_transform).intersects(&player_volume) {
+    for (enemy_entity, mut enemy, hunter, enemy_transform) in enemy_query.iter_mut() {
+        enemy.contact_timer.tick(time.delta());
+
+        if enemy.enemy_state == EnemyState::Dead
+            || hunter.is_some_and(Hunter::is_latched)
+            || !enemy.volume(enemy_transform).intersects(&player_volume)
+        {
+            continue;
+        }
+
+        // One shove and one ram per contact, however many frames it lasts.
+        if enemy.contact_timer.finished() {
+            let away = (enemy_transform.translation - player_transform.translation)
+                .truncate()
+                .normalize_or(player.direction);
+
+            knockback_events.send(KnockbackEvent {
+                force: away * player.ram_force,
+                target: enemy_entity,
+            });
+
+            if player.ram_damage > 0 {
+                damage_events.send(DamageEvent {
+                    amount: player.ram_damage,
+                    damage_type: DamageType::Blunt,
+                    source: Some(player_entity),
+                    target: enemy_entity,
+                });
+            }
+
+            enemy.contact_timer.reset();
+        }
+
+        if player.player_state == PlayerState::Invincible || enemy.damage == 0 {
             continue;
         }
 
-        health.current = health.current.saturating_sub(enemy.damage);
+        damage_events.send(DamageEvent {
+            amount: enemy.damage,
+            damage_type: DamageType::Blunt,
+            source: Some(enemy_entity),
+            target: player_entity,
+        });
         player.player_state = PlayerState::Invincible;
         player.invincibility_timer.reset();
     }
@@ -206,14 +425,19 @@ pub struct EnemyPlugin;
 impl Plugin for EnemyPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(OnExit(WaveState::Running), destroy_enemies);
+        app.init_resource::<SpatialGrid>();
         app.add_systems(
             Update,
             (
-                enemy_behavior,
+                enemy_animation_finished.before(enemy_death),
+                enemy_animation_parameters.after(enemy_behavior).after(enemy_death),
+                enemy_behavior.after(update_spatial_grid),
                 enemy_death,
                 enemy_movement,
+                enemy_obstacles.after(enemy_movement).after(knockback_movement),
                 initialize_enemy,
                 player_hit,
+                update_spatial_grid,
             ).in_set(PausableSet).in_set(WaveRunningSet),
         );
     }
@@ -0,0 +1,137 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::arena_map::Hazard;
+use crate::game::game_sets::PausableSet;
+use crate::health::{DamageEvent, DamageType};
+
+use super::enemy::Enemy;
+use super::status_effect::{StatusEffect, StatusEffectEvent, StatusEffectKind};
+use super::tile_collision::TileCollision;
+use super::wave_sets::WaveRunningSet;
+
+const HAZARD_TICK_RATE: f32 = 1.0;
+const ICE_TURN_MULTIPLIER: f32 = 0.3;
+const LAVA_BURN_DAMAGE: f32 = 1.0;
+const LAVA_BURN_DURATION: f32 = 3.0;
+const MUD_SPEED_MULTIPLIER: f32 = 0.5;
+const SPEED_PAD_MULTIPLIER: f32 = 1.6;
+const SPIKE_DAMAGE: u32 = 1;
+
+/// Marks a tile with an effect on whatever moves over it.
+#[derive(Component)]
+pub struct HazardTile(pub Hazard);
+
+/// The hazard, if any, under something that moves across the arena.
+#[derive(Component)]
+pub struct Footing {
+    hazard: Option<Hazard>,
+    tick_timer: Timer,
+}
+
+impl Default for Footing {
+    fn default() -> Self {
+        Self {
+            hazard: None,
+            tick_timer: Self::tick_timer(),
+        }
+    }
+}
+
+impl Footing {
+    /// A timer that finishes on its first tick, so hazards bite as soon as they are stepped on.
+    fn tick_timer() -> Timer {
+        let mut timer = Timer::from_seconds(HAZARD_TICK_RATE, TimerMode::Repeating);
+
+        timer.set_elapsed(Duration::from_secs_f32(HAZARD_TICK_RATE));
+        timer
+    }
+
+    /// What movement speed should be scaled by.
+    pub fn speed_multiplier(&self) -> f32 {
+        match self.hazard {
+            Some(Hazard::Mud) => MUD_SPEED_MULTIPLIER,
+            Some(Hazard::SpeedPad) => SPEED_PAD_MULTIPLIER,
+            _ => 1.0,
+        }
+    }
+
+    /// What turn rate should be scaled by.
+    pub fn turn_multiplier(&self) -> f32 {
+        match self.hazard {
+            Some(Hazard::Ice) => ICE_TURN_MULTIPLIER,
+            _ => 1.0,
+        }
+    }
+}
+
+/// Spikes hurt anything on them, and lava sets enemies alight, once a second for as long as they
+/// stay on it.
+fn hazard_damage(
+    mut damage_events: EventWriter<DamageEvent>,
+    enemy_query: Query<(), With<Enemy>>,
+    mut query: Query<(Entity, &mut Footing)>,
+    mut status_effect_events: EventWriter<StatusEffectEvent>,
+    time: Res<Time>,
+) {
+    for (entity, mut footing) in query.iter_mut() {
+        let Some(hazard) = footing.hazard else {
+            continue;
+        };
+
+        footing.tick_timer.tick(time.delta());
+
+        if !footing.tick_timer.just_finished() {
+            continue;
+        }
+
+        match hazard {
+            Hazard::Lava if enemy_query.contains(entity) => {
+                status_effect_events.send(StatusEffectEvent {
+                    effect: StatusEffect::new(
+                        StatusEffectKind::Burn,
+                        LAVA_BURN_DURATION,
+                        LAVA_BURN_DAMAGE,
+                    ),
+                    source: None,
+                    target: entity,
+                });
+            }
+            Hazard::Spikes => {
+                damage_events.send(DamageEvent {
+                    amount: SPIKE_DAMAGE,
+                    damage_type: DamageType::Pierce,
+                    source: None,
+                    target: entity,
+                });
+            }
+            _ => {}
+        }
+    }
+}
+
+fn update_footing(mut query: Query<(&mut Footing, &Transform)>, tiles: TileCollision) {
+    for (mut footing, transform) in query.iter_mut() {
+        let hazard = tiles.hazard(transform.translation.xy());
+
+        if footing.hazard != hazard {
+            footing.hazard = hazard;
+            footing.tick_timer = Footing::tick_timer();
+        }
+    }
+}
+
+pub struct HazardPlugin;
+
+impl Plugin for HazardPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (update_footing, hazard_damage)
+                .chain()
+                .in_set(PausableSet)
+                .in_set(WaveRunningSet),
+        );
+    }
+}
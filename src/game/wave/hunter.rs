@@ -0,0 +1,303 @@
+use bevy::{math::bounding::*, prelude::*};
+use bevy_prng::WyRand;
+use bevy_rand::prelude::*;
+use rand::seq::IteratorRandom;
+
+use crate::asset_handles::AssetHandles;
+use crate::colors::DARK_RED;
+use crate::game::game_sets::PausableSet;
+use crate::health::{DamageEvent, DamageType, Health, HealthState};
+
+use super::enemy::{Enemy, EnemyState, EnemyType, enemy_movement};
+use super::knockback::{Knockback, knockback_movement};
+use super::player::{Player, defender::Defender};
+use super::status_effect::StatusEffects;
+use super::tile_collision::TileCollision;
+use super::wave_sets::WaveRunningSet;
+use super::wave_state::WaveState;
+
+/// How far outside the keep a saboteur can grab hold of a defender.
+const LATCH_RADIUS: f32 = 4.0;
+const SABOTAGE_DAMAGE: u32 = 1;
+const SABOTAGE_RATE: f32 = 0.75;
+const SHOT_DAMAGE: u32 = 1;
+const SHOT_FRAME: usize = 1;
+const SHOT_HIT_DISTANCE: f32 = 4.0;
+const SHOT_LIFETIME: f32 = 3.0;
+const SHOT_SPEED: f32 = 160.0;
+const SNIPER_FIRE_RATE: f32 = 2.0;
+const SNIPER_RANGE: f32 = 160.0;
+
+/// An enemy that goes after the keep's crew rather than the keep itself.
+#[derive(Component)]
+#[require(Enemy)]
+pub struct Hunter {
+    attack_timer: Timer,
+    /// The defender a saboteur is hanging on to.
+    latched: Option<Entity>,
+}
+
+impl Hunter {
+    /// Whether this is a saboteur hanging on to a defender.
+    pub fn is_latched(&self) -> bool {
+        self.latched.is_some()
+    }
+
+    fn new(attack_rate: f32) -> Self {
+        Self {
+            attack_timer: Timer::from_seconds(attack_rate, TimerMode::Repeating),
+            latched: None,
+        }
+    }
+}
+
+/// A sniper's shot, homing in on one defender.
+#[derive(Component)]
+#[require(Sprite, Transform, Visibility)]
+struct EnemyShot {
+    lifetime: Timer,
+    source: Entity,
+    target: Entity,
+}
+
+fn destroy_enemy_shots(mut commands: Commands, query: Query<Entity, With<EnemyShot>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn enemy_shot_movement(
+    mut commands: Commands,
+    mut damage_events: EventWriter<DamageEvent>,
+    defender_query: Query<(&GlobalTransform, &Health), With<Defender>>,
+    mut shot_query: Query<(Entity, &mut EnemyShot, &mut Transform)>,
+    tiles: TileCollision,
+    time: Res<Time>,
+) {
+    for (shot_entity, mut shot, mut shot_transform) in shot_query.iter_mut() {
+        shot.lifetime.tick(time.delta());
+
+        let Ok((target_transform, target_health)) = defender_query.get(shot.target) else {
+            commands.entity(shot_entity).despawn();
+            continue;
+        };
+
+        if shot.lifetime.finished() || target_health.state == HealthState::Dead {
+            commands.entity(shot_entity).despawn();
+            continue;
+        }
+
+        let offset = target_transform.translation().xy() - shot_transform.translation.xy();
+
+        if offset.length() <= SHOT_HIT_DISTANCE {
+            damage_events.send(DamageEvent {
+                amount: SHOT_DAMAGE,
+                damage_type: DamageType::Pierce,
+                source: Some(shot.source),
+                target: shot.target,
+            });
+            commands.entity(shot_entity).despawn();
+            continue;
+        }
+
+        let direction = offset.normalize();
+        let step = direction * (SHOT_SPEED * time.delta_secs()).min(offset.length());
+
+        // Obstacles give the crew cover.
+        if tiles.is_solid(shot_transform.translation.xy() + step) {
+            commands.entity(shot_entity).despawn();
+            continue;
+        }
+
+        shot_transform.translation += step.extend(0.0);
+        shot_transform.rotation = Quat::from_rotation_arc(Vec3::Y, direction.extend(0.0));
+    }
+}
+
+fn initialize_hunter(mut commands: Commands, query: Query<(Entity, &Enemy), Added<Enemy>>) {
+    for (enemy_entity, enemy) in query.iter() {
+        let attack_rate = match enemy.enemy_type {
+            EnemyType::Saboteur => SABOTAGE_RATE,
+            EnemyType::Sniper => SNIPER_FIRE_RATE,
+            EnemyType::Brute | EnemyType::Normal => continue,
+        };
+
+        commands
+            .entity(enemy_entity)
+            .insert(Hunter::new(attack_rate));
+    }
+}
+
+/// Saboteurs that reach the keep grab the nearest defender still standing and ride along,
+/// hurting it until they are killed, stunned off or it goes down.
+fn saboteur_latch(
+    mut damage_events: EventWriter<DamageEvent>,
+    defender_query: Query<(Entity, &GlobalTransform, &Health), With<Defender>>,
+    mut hunter_query: Query<
+        (
+            Entity,
+            &mut Hunter,
+            &Enemy,
+            &mut Knockback,
+            &StatusEffects,
+            &mut Transform,
+        ),
+        Without<Player>,
+    >,
+    player_query: Query<(&Player, &Transform)>,
+    time: Res<Time>,
+) {
+    let Ok((player, player_transform)) = player_query.get_single() else {
+        return;
+    };
+
+    let latch_volume = BoundingCircle::new(
+        player_transform.translation.xy(),
+        player.volume(player_transform).radius() + LATCH_RADIUS,
+    );
+
+    for (hunter_entity, mut hunter, enemy, mut knockback, status_effects, mut transform) in
+        hunter_query.iter_mut()
+    {
+        if enemy.enemy_type != EnemyType::Saboteur {
+            continue;
+        }
+
+        if enemy.enemy_state != EnemyState::Active || status_effects.is_stunned() {
+            hunter.latched = None;
+            continue;
+        }
+
+        let Some(defender_entity) = hunter.latched else {
+            if !enemy.volume(&transform).intersects(&latch_volume) {
+                continue;
+            }
+
+            let position = transform.translation.xy();
+
+            hunter.latched = defender_query
+                .iter()
+                .filter(|(_, _, health)| health.state == HealthState::Alive)
+                .min_by(|(_, a, _), (_, b, _)| {
+                    let a = a.translation().xy().distance_squared(position);
+                    let b = b.translation().xy().distance_squared(position);
+
+                    a.total_cmp(&b)
+                })
+                .map(|(entity, _, _)| entity);
+            hunter.attack_timer.reset();
+            continue;
+        };
+
+        let Ok((_, defender_transform, defender_health)) = defender_query.get(defender_entity)
+        else {
+            hunter.latched = None;
+            continue;
+        };
+
+        if defender_health.state == HealthState::Dead {
+            hunter.latched = None;
+            continue;
+        }
+
+        let defender_position = defender_transform.translation().xy();
+
+        transform.translation.x = defender_position.x;
+        transform.translation.y = defender_position.y;
+        // Riding along, so nothing should be left to throw it off once it lets go.
+        knockback.stop();
+
+        hunter.attack_timer.tick(time.delta());
+
+        if hunter.attack_timer.just_finished() {
+            damage_events.send(DamageEvent {
+                amount: SABOTAGE_DAMAGE,
+                damage_type: DamageType::Blunt,
+                source: Some(hunter_entity),
+                target: defender_entity,
+            });
+        }
+    }
+}
+
+/// Snipers in range of the keep take aim at a random defender that is still standing.
+fn sniper_fire(
+    asset_handles: Res<AssetHandles>,
+    mut commands: Commands,
+    defender_query: Query<(Entity, &Health), With<Defender>>,
+    mut global_rng: GlobalEntropy<WyRand>,
+    mut hunter_query: Query<(Entity, &mut Hunter, &Enemy, &StatusEffects, &Transform)>,
+    player_query: Query<&Transform, With<Player>>,
+    time: Res<Time>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+
+    for (hunter_entity, mut hunter, enemy, status_effects, transform) in hunter_query.iter_mut() {
+        if enemy.enemy_type != EnemyType::Sniper
+            || enemy.enemy_state != EnemyState::Active
+            || status_effects.is_stunned()
+        {
+            continue;
+        }
+
+        hunter.attack_timer.tick(time.delta());
+
+        let distance = transform
+            .translation
+            .xy()
+            .distance(player_transform.translation.xy());
+
+        if !hunter.attack_timer.just_finished() || distance > SNIPER_RANGE {
+            continue;
+        }
+
+        let Some(target) = defender_query
+            .iter()
+            .filter(|(_, health)| health.state == HealthState::Alive)
+            .map(|(entity, _)| entity)
+            .choose(&mut global_rng.fork_rng())
+        else {
+            continue;
+        };
+
+        let mut sprite = Sprite::from_atlas_image(
+            asset_handles.image("weapon"),
+            asset_handles.texture_atlas("weapon", SHOT_FRAME),
+        );
+
+        sprite.color = DARK_RED;
+
+        commands.spawn((
+            EnemyShot {
+                lifetime: Timer::from_seconds(SHOT_LIFETIME, TimerMode::Once),
+                source: hunter_entity,
+                target,
+            },
+            sprite,
+            Transform::from_translation(transform.translation.xy().extend(0.5)),
+        ));
+    }
+}
+
+pub struct HunterPlugin;
+
+impl Plugin for HunterPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnExit(WaveState::Running), destroy_enemy_shots);
+        app.add_systems(
+            Update,
+            (
+                enemy_shot_movement,
+                initialize_hunter,
+                saboteur_latch
+                    .after(enemy_movement)
+                    .after(knockback_movement),
+                sniper_fire,
+            )
+                .in_set(PausableSet)
+                .in_set(WaveRunningSet),
+        );
+    }
+}
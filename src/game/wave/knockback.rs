@@ -0,0 +1,77 @@
+use bevy::prelude::*;
+
+use crate::game::game_sets::PausableSet;
+
+use super::wave_sets::WaveRunningSet;
+
+/// How quickly knockback dies down, as a fraction of its speed lost per second.
+const KNOCKBACK_DAMPING: f32 = 8.0;
+const MIN_KNOCKBACK_SPEED: f32 = 1.0;
+
+/// Lets an entity be pushed around by `KnockbackEvent`s.
+#[derive(Component, Default)]
+pub struct Knockback {
+    /// How much of any push is ignored, from 0 to 1. Heavy enemies resist most of it.
+    pub resistance: f32,
+    velocity: Vec2,
+}
+
+impl Knockback {
+    /// Drops any push still being carried out.
+    pub fn stop(&mut self) {
+        self.velocity = Vec2::ZERO;
+    }
+}
+
+/// A push applied to `target`, in units per second.
+#[derive(Event)]
+pub struct KnockbackEvent {
+    pub force: Vec2,
+    pub target: Entity,
+}
+
+fn apply_knockback(
+    mut knockback_events: EventReader<KnockbackEvent>,
+    mut query: Query<&mut Knockback>,
+) {
+    for event in knockback_events.read() {
+        let Ok(mut knockback) = query.get_mut(event.target) else {
+            continue;
+        };
+
+        let resistance = knockback.resistance.clamp(0.0, 1.0);
+
+        knockback.velocity += event.force * (1.0 - resistance);
+    }
+}
+
+pub fn knockback_movement(mut query: Query<(&mut Knockback, &mut Transform)>, time: Res<Time>) {
+    for (mut knockback, mut transform) in query.iter_mut() {
+        if knockback.velocity == Vec2::ZERO {
+            continue;
+        }
+
+        transform.translation += (knockback.velocity * time.delta_secs()).extend(0.0);
+
+        knockback.velocity *= (-KNOCKBACK_DAMPING * time.delta_secs()).exp();
+
+        if knockback.velocity.length() < MIN_KNOCKBACK_SPEED {
+            knockback.velocity = Vec2::ZERO;
+        }
+    }
+}
+
+pub struct KnockbackPlugin;
+
+impl Plugin for KnockbackPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<KnockbackEvent>();
+        app.add_systems(
+            Update,
+            (apply_knockback, knockback_movement)
+                .chain()
+                .in_set(PausableSet)
+                .in_set(WaveRunningSet),
+        );
+    }
+}
@@ -0,0 +1,99 @@
+use bevy::{math::bounding::*, prelude::*};
+
+use crate::colors::GOLD;
+use crate::game::{game_controller::GameController, game_sets::PausableSet};
+use crate::health::Died;
+
+use super::elite::Elite;
+use super::enemy::Enemy;
+use super::player::Player;
+use super::wave_sets::WaveRunningSet;
+use super::wave_state::WaveState;
+
+const LOOT_SIZE: f32 = 4.0;
+/// How far outside the keep loot is still picked up.
+const PICKUP_RADIUS: f32 = 8.0;
+
+/// Gold dropped by a dead enemy or a smashed prop, collected by driving the keep over it.
+#[derive(Component)]
+#[require(Sprite, Transform, Visibility)]
+pub struct Loot {
+    pub value: u32,
+}
+
+impl Loot {
+    /// Drops `value` gold at `position`, drawn bigger the more it is worth.
+    pub fn spawn(commands: &mut Commands, value: u32, position: Vec2) {
+        commands.spawn((
+            Loot { value },
+            Sprite::from_color(GOLD, Vec2::splat(LOOT_SIZE + value.min(4) as f32)),
+            Transform::from_translation(position.extend(-0.25)),
+        ));
+    }
+}
+
+fn collect_loot(
+    mut commands: Commands,
+    mut game_controller: ResMut<GameController>,
+    loot_query: Query<(Entity, &Loot, &Transform)>,
+    player_query: Query<(&Player, &Transform)>,
+) {
+    let Ok((player, player_transform)) = player_query.get_single() else {
+        return;
+    };
+
+    let pickup_volume = BoundingCircle::new(
+        player_transform.translation.xy(),
+        player.volume(player_transform).radius() + PICKUP_RADIUS,
+    );
+
+    for (loot_entity, loot, loot_transform) in loot_query.iter() {
+        if !pickup_volume.intersects(&BoundingCircle::new(
+            loot_transform.translation.xy(),
+            LOOT_SIZE,
+        )) {
+            continue;
+        }
+
+        game_controller.gold += loot.value;
+        commands.entity(loot_entity).despawn();
+    }
+}
+
+fn destroy_loot(mut commands: Commands, query: Query<Entity, With<Loot>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+/// Elites are worth more for every affix they rolled.
+fn drop_loot(
+    mut commands: Commands,
+    mut died_events: EventReader<Died>,
+    enemy_query: Query<(&Enemy, Option<&Elite>, &Transform)>,
+) {
+    for event in died_events.read() {
+        let Ok((enemy, elite, transform)) = enemy_query.get(event.entity) else {
+            continue;
+        };
+
+        let affixes = elite.map_or(0, |elite| elite.affixes.len() as u32);
+        let value = enemy.enemy_type.loot_value() * (1 + affixes);
+
+        Loot::spawn(&mut commands, value, transform.translation.xy());
+    }
+}
+
+pub struct LootPlugin;
+
+impl Plugin for LootPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnExit(WaveState::Running), destroy_loot);
+        app.add_systems(
+            Update,
+            (collect_loot, drop_loot)
+                .in_set(PausableSet)
+                .in_set(WaveRunningSet),
+        );
+    }
+}
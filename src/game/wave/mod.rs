@@ -1,39 +1,64 @@
+mod announcement;
+mod arena;
+mod arena_shift;
+mod elite;
 mod enemy;
+mod hazard;
+mod hunter;
+mod knockback;
+mod loot;
+mod open_world;
 mod player;
+mod projectile;
+mod prop;
+mod spawn_zone;
+mod status_effect;
+mod steering;
+mod tile_collision;
 mod wave_controller;
 mod wave_sets;
 mod wave_state;
 
-use bevy::{audio::*, math::bounding::*, prelude::*};
+use bevy::{audio::*, math::bounding::BoundingVolume, prelude::*};
 use bevy_ecs_tilemap::prelude::*;
 use bevy_prng::WyRand;
+use announcement::{Announcement, AnnouncementPlugin, AnnouncementStyle};
+use arena::{AREA_SIZE, Arena, ArenaTile, TILE_SIZE};
+use arena_shift::{ArenaShift, ArenaShiftPlugin};
 use bevy_rand::prelude::*;
-use enemy::{Enemy, EnemyPlugin};
-use player::{Player, PlayerPlugin, PlayerState, PLAYER_SIZE};
-use rand::{Rng, seq::IteratorRandom};
-use wave_controller::{wave_timer_tick, WaveController};
+use elite::{Elite, ElitePlugin};
+use enemy::{Enemy, EnemyPlugin, EnemyType};
+use hazard::{HazardPlugin, HazardTile};
+use hunter::HunterPlugin;
+use knockback::KnockbackPlugin;
+use loot::LootPlugin;
+use open_world::{OpenWorld, OpenWorldPlugin};
+use player::{Player, PlayerPlugin, PlayerState};
+use projectile::ProjectilePlugin;
+use prop::{PROP_DAMAGE_PER_SPEED, PropPlugin, PropTile};
+use rand::{Rng, seq::SliceRandom};
+use spawn_zone::{SpawnZone, SpawnZonePlugin};
+use status_effect::{StatusEffect, StatusEffectEvent, StatusEffectKind, StatusEffectPlugin};
+use steering::MAX_FLANK_ANGLE;
+use tile_collision::{SolidTile, TileCollision};
+use wave_controller::{wave_timer_tick, WaveController, TRANSITION_RATE};
 use wave_sets::WaveRunningSet;
 use wave_state::WaveState;
 
-use crate::{asset_handles::AssetHandles, health::Health};
+use crate::{arena_map::ArenaMap, asset_handles::AssetHandles, autotile::AutotileRules, game_mode::GameMode, health::{DamageEvent, DamageType, Health}, leaderboard::{EndlessLeaderboard, format_time}, settings::Settings, widgets};
 
-use super::{game_controller::GameController, game_state::GameState};
+use super::{game_controller::GameController, game_sets::PausableSet, game_state::GameState};
 
-const AREA_SIZE: UVec2 = UVec2::new(128, 64);
-const ARENA_SIZE: UVec2 = UVec2::new(48, 24);
 const ARENA_BOUNDARY_OFFSET: u32 = 7;
-const TILE_SIZE: f32 = 16.0;
+/// Wall damage for every unit per second the keep was driving into it.
+const WALL_DAMAGE_PER_SPEED: f32 = 0.025;
+const WALL_STUN_DURATION: f32 = 0.5;
 
 #[derive(Component)]
-struct FinishedMessage;
-
-#[derive(Component)]
-struct GameOverMessage;
+struct GoldUi;
 
 #[derive(Component)]
 struct HealthUi;
-#[derive(Component)]
-struct PreparationMessage;
 
 #[derive(Component)]
 struct WaveTilemap;
@@ -44,59 +69,99 @@ struct WaveTimerUi;
 #[derive(Component)]
 struct WaveUi;
 
-#[derive(Resource)]
-struct Arena {
-    area: URect,
-    playable_area: Aabb2d,
-}
+/// In hardcore the keep is lost as soon as it runs into a wall or an obstacle. Otherwise it bounces
+/// back off, hurt and stunned by how hard it hit. Props it hits hard enough, or at all in hardcore,
+/// are smashed, and it carries on straight through them.
+fn boundary_collision(mut damage_events: EventWriter<DamageEvent>, prop_query: Query<&Health, With<PropTile>>, mut query: Query<(Entity, &mut Player, &mut Transform)>, settings: Res<Settings>, mut status_effect_events: EventWriter<StatusEffectEvent>, tiles: TileCollision) {
+    let Ok((player_entity, mut player, mut transform)) = query.get_single_mut() else {
+        return;
+    };
 
-impl Default for Arena {
-    fn default() -> Self {
-        let total_area = URect::from_corners(UVec2::ZERO, AREA_SIZE);
+    if player.player_state == PlayerState::Dead {
+        return;
+    }
+
+    let volume = player.volume(transform.as_ref());
+
+    let mut smashed = Vec::new();
+
+    for (tile_entity, tile) in tiles.overlapping(&volume) {
+        let Ok(health) = prop_query.get(tile_entity) else {
+            continue;
+        };
 
-        let area = URect::from_center_size(total_area.center(), ARENA_SIZE);
+        let impact = player.direction.dot((tile.center() - volume.center).normalize_or_zero());
+        let mut amount = (impact * player.speed * PROP_DAMAGE_PER_SPEED).round() as u32;
 
-        Self {
-            area,
-            playable_area: Aabb2d::new(Vec2::ZERO, (area.half_size().as_vec2() * TILE_SIZE) - (PLAYER_SIZE * 1.4)),
+        // In hardcore props are there to be broken rather than walls, so anything touched goes.
+        if settings.hardcore {
+            amount = amount.max(health.current);
         }
-    }
-}
 
-fn boundary_collision(arena: Res<Arena>, mut query: Query<(&mut Player, &Transform)>) {
-    let Ok((mut player, transform)) = query.get_single_mut() else {
-        return;
-    };
+        if amount == 0 {
+            continue;
+        }
 
-    if !arena.playable_area.intersects(&player.volume(transform)) {
-        player.player_state = PlayerState::Dead;
+        damage_events.send(DamageEvent {
+            amount,
+            damage_type: DamageType::Blunt,
+            source: Some(player_entity),
+            target: tile_entity,
+        });
+
+        if health.mitigate(amount, DamageType::Blunt) >= health.current {
+            smashed.push(tile_entity);
+        }
     }
-}
 
-fn destroy_finished(mut commands: Commands, query: Query<Entity, With<FinishedMessage>>) {
-    for entity in query.iter() {
-        commands.entity(entity).despawn_recursive();
+    let push = tiles.push_out_ignoring(&volume, &smashed);
+
+    if settings.hardcore {
+        if push != Vec2::ZERO {
+            player.player_state = PlayerState::Dead;
+        }
+
+        return;
     }
-}
 
-fn destroy_game_over(mut commands: Commands, query: Query<Entity, With<GameOverMessage>>) {
-    for entity in query.iter() {
-        commands.entity(entity).despawn_recursive();
+    if push == Vec2::ZERO {
+        return;
     }
-}
 
-fn destroy_preparation(mut commands: Commands, query: Query<Entity, With<PreparationMessage>>) {
-    for entity in query.iter() {
-        commands.entity(entity).despawn_recursive();
+    let normal = push.normalize();
+    let impact = -player.direction.dot(normal);
+
+    transform.translation += push.extend(0.0);
+
+    // Already heading back in, such as when sliding along a wall.
+    if impact <= 0.0 {
+        return;
     }
+
+    player.direction = player.direction.reflect(normal);
+
+    damage_events.send(DamageEvent {
+        amount: (impact * player.speed * WALL_DAMAGE_PER_SPEED).round().max(1.0) as u32,
+        damage_type: DamageType::Blunt,
+        source: None,
+        target: player_entity,
+    });
+    status_effect_events.send(StatusEffectEvent {
+        effect: StatusEffect::new(StatusEffectKind::Stun, WALL_STUN_DURATION, 0.0),
+        source: None,
+        target: player_entity,
+    });
 }
 
 fn destroy_wave(
     audio_query: Query<Entity, With<AudioPlayer>>,
     mut commands: Commands,
+    tilemap_query: Query<(Entity, &TileStorage), With<WaveTilemap>>,
     wave_ui_query: Query<Entity, With<WaveUi>>,
 ) {
     // Wave controller
+    commands.remove_resource::<ArenaShift>();
+    commands.remove_resource::<OpenWorld>();
     commands.remove_resource::<WaveController>();
 
     for entity in audio_query.iter() {
@@ -106,28 +171,82 @@ fn destroy_wave(
     for entity in wave_ui_query.iter() {
         commands.entity(entity).despawn_recursive();
     }
+
+    // Tiles aren't children of their tilemap, so go through its storage
+    for (tilemap_entity, tile_storage) in tilemap_query.iter() {
+        for tile_entity in tile_storage.iter().flatten() {
+            commands.entity(*tile_entity).despawn();
+        }
+
+        commands.entity(tilemap_entity).despawn_recursive();
+    }
+}
+
+fn announce_finished(mut announcement_events: EventWriter<Announcement>) {
+    announcement_events.send(
+        Announcement::new("Finished!")
+            .with_duration(TRANSITION_RATE)
+            .with_priority(1),
+    );
 }
 
-fn prepare(
-    mut preparation_message_query: Query<&Children, With<PreparationMessage>>,
-    mut text_query: Query<&mut Text>,
+fn announce_game_over(
+    mut announcement_events: EventWriter<Announcement>,
     wave_controller: Res<WaveController>,
 ) {
-    let Ok(children) = preparation_message_query.get_single_mut() else {
+    // Endless runs announce how long they lasted instead.
+    if wave_controller.endless {
         return;
-    };
+    }
 
-    for child in children.iter() {
-        let Ok(mut text) = text_query.get_mut(*child) else {
-            continue;
-        };
+    announcement_events.send(
+        Announcement::new("Game Over!")
+            .with_duration(TRANSITION_RATE)
+            .with_priority(2)
+            .with_style(AnnouncementStyle::Danger),
+    );
+}
 
-        text.0 = if wave_controller.preparation_state == 0 {
-            "Go!".to_string()
-        } else {
-            wave_controller.preparation_state.to_string()
-        };
+fn announce_preparation(
+    mut announcement_events: EventWriter<Announcement>,
+    wave_controller: Res<WaveController>,
+) {
+    announcement_events.send(wave_controller.countdown_announcement());
+}
+
+/// Endless runs are scored by how long the keep lasted.
+fn record_endless_time(
+    mut announcement_events: EventWriter<Announcement>,
+    mut leaderboard: ResMut<EndlessLeaderboard>,
+    wave_controller: Res<WaveController>,
+) {
+    if !wave_controller.endless {
+        return;
     }
+
+    let time = wave_controller.survived.elapsed_secs();
+    let message = match leaderboard.record(time) {
+        Some(0) => format!("New best: {}!", format_time(time)),
+        _ => format!("Survived {}", format_time(time)),
+    };
+
+    announcement_events.send(
+        Announcement::new(message)
+            .with_duration(TRANSITION_RATE)
+            .with_priority(2)
+            .with_style(AnnouncementStyle::Danger),
+    );
+}
+
+fn gold_ui(
+    game_controller: Res<GameController>,
+    mut text_query: Query<&mut Text, With<GoldUi>>,
+) {
+    let Ok(mut text) = text_query.get_single_mut() else {
+        return;
+    };
+
+    text.0 = format!("Gold: {}", game_controller.gold);
 }
 
 fn health_ui(
@@ -145,110 +264,115 @@ fn health_ui(
     text.0 = format!("HP: {}/{}", health.current, health.max);
 }
 
-fn setup_preparation(asset_handles: Res<AssetHandles>, mut commands: Commands) {
-    commands
-        .spawn((
-            BackgroundColor(Color::BLACK),
-            Node {
-                align_items: AlignItems::Center,
-                display: Display::Flex,
-                justify_content: JustifyContent::Center,
-                left: Val::Percent(50.0),
-                padding: UiRect::all(Val::Px(16.0)),
-                position_type: PositionType::Absolute,
-                top: Val::Percent(50.0),
-                ..default()
-            },
-            PreparationMessage,
-        ))
-        .with_children(|parent| {
-            parent.spawn((
-                Text::new("3"),
-                TextColor(Color::WHITE),
-                TextFont {
-                    font: asset_handles.font_map.get("default").unwrap().clone(),
-                    ..default()
-                },
-            ));
-        });
-}
+/// Spawns a tilemap `size` tiles across whose bottom left tile is centred on `origin`, with `tile`
+/// giving each tile's terrain and texture.
+fn spawn_tilemap(commands: &mut Commands, asset_handles: &AssetHandles, size: UVec2, origin: Vec2, mut tile: impl FnMut(UVec2) -> (ArenaTile, TileTextureIndex)) -> Entity {
+    let tilemap_entity = commands.spawn_empty().id();
 
-fn setup_finished(asset_handles: Res<AssetHandles>, mut commands: Commands) {
-    commands
-        .spawn((
-            BackgroundColor(Color::BLACK),
-            FinishedMessage,
-            Node {
-                align_items: AlignItems::Center,
-                display: Display::Flex,
-                justify_content: JustifyContent::Center,
-                left: Val::Percent(50.0),
-                padding: UiRect::all(Val::Px(16.0)),
-                position_type: PositionType::Absolute,
-                top: Val::Percent(50.0),
-                ..default()
-            },
-        ))
-        .with_children(|parent| {
-            parent.spawn((
-                Text::new("Finished!"),
-                TextColor(Color::WHITE),
-                TextFont {
-                    font: asset_handles.font_map.get("default").unwrap().clone(),
-                    ..default()
-                },
-            ));
-        });
-}
+    let mut tile_storage = TileStorage::empty(size.into());
 
-fn setup_game_over(asset_handles: Res<AssetHandles>, mut commands: Commands) {
-    commands
-        .spawn((
-            BackgroundColor(Color::BLACK),
-            GameOverMessage,
-            Node {
-                align_items: AlignItems::Center,
-                display: Display::Flex,
-                justify_content: JustifyContent::Center,
-                left: Val::Percent(50.0),
-                padding: UiRect::all(Val::Px(16.0)),
-                position_type: PositionType::Absolute,
-                top: Val::Percent(50.0),
-                ..default()
-            },
-        ))
-        .with_children(|parent| {
-            parent.spawn((
-                Text::new("Game Over!"),
-                TextColor(Color::WHITE),
-                TextFont {
-                    font: asset_handles.font_map.get("default").unwrap().clone(),
-                    ..default()
-                },
-            ));
-        });
+    let tilemap_id = TilemapId(tilemap_entity);
+
+    for x in 0..size.x {
+        for y in 0..size.y {
+            let tile_pos = TilePos { x, y };
+            let (arena_tile, texture_index) = tile(UVec2::new(x, y));
+            let mut tile_commands = commands
+                .spawn(TileBundle {
+                    position: tile_pos,
+                    texture_index,
+                    tilemap_id,
+                    ..Default::default()
+                });
+
+            match arena_tile {
+                ArenaTile::Hazard(hazard) => {
+                    tile_commands.insert(HazardTile(hazard));
+                }
+                ArenaTile::Prop(prop) => {
+                    tile_commands.insert((PropTile(prop), SolidTile));
+                }
+                tile if tile.is_solid() => {
+                    tile_commands.insert(SolidTile);
+                }
+                _ => {}
+            }
+
+            let tile_entity = tile_commands.id();
+            tile_storage.set(&tile_pos, tile_entity);
+        }
+    }
+
+    let tile_size = TilemapTileSize {
+        x: TILE_SIZE,
+        y: TILE_SIZE,
+    };
+
+    commands.entity(tilemap_entity).insert((
+        TilemapBundle {
+            grid_size: tile_size.into(),
+            map_type: TilemapType::Square,
+            size: size.into(),
+            storage: tile_storage,
+            texture: TilemapTexture::Single(asset_handles.image("terrain")),
+            tile_size,
+            transform: Transform::from_translation(origin.extend(-1.0)),
+            ..default()
+        },
+        WaveTilemap,
+    ));
+
+    tilemap_entity
 }
 
 fn setup_wave(
-    arena: Res<Arena>,
+    arena_maps: Res<Assets<ArenaMap>>,
     asset_handles: Res<AssetHandles>,
-    asset_server: Res<AssetServer>,
     mut commands: Commands,
     game_controller: Res<GameController>,
+    game_mode: Res<GameMode>,
     mut global_rng: GlobalEntropy<WyRand>,
     mut query: Query<&mut Transform, With<Camera>>,
+    settings: Res<Settings>,
 ) {
-    commands.insert_resource(WaveController::from_level(game_controller.wave_level));
+    let maps = asset_handles.arenas().into_iter().filter_map(|handle| arena_maps.get(handle)).collect::<Vec<_>>();
+    let arena = if settings.open_world {
+        Arena::open_world()
+    } else {
+        Arena::for_wave(game_controller.seed, game_controller.wave_level, &maps)
+    };
+
+    let mut rng = global_rng.fork_rng();
+
+    let mut wave_controller = if *game_mode == GameMode::Endless {
+        WaveController::endless()
+    } else {
+        WaveController::from_level(game_controller.wave_level)
+    };
+
+    if settings.open_world {
+        // The world has no middle for authored points to be placed around.
+        wave_controller.spawn_zones.retain(|spawn_zone| !matches!(spawn_zone, SpawnZone::Points(_)));
+    } else if !arena.spawn_points.is_empty() {
+        wave_controller.spawn_zones = vec![SpawnZone::Points(arena.spawn_points.clone())];
+    }
+
+    if !settings.open_world && rng.gen_bool(wave_controller.arena_shift_chance as f64) {
+        commands.insert_resource(ArenaShift::roll(&mut rng, &arena));
+    }
+
+    commands.insert_resource(wave_controller);
 
     // Start music
     #[cfg(not(target_family = "wasm"))]
-    let bgm_handle = asset_server.load("sounds/bgm.wav");
+    let bgm_handle = asset_handles.audio("bgm");
 
     #[cfg(not(target_family = "wasm"))]
     commands.spawn((
         AudioPlayer::new(bgm_handle),
         PlaybackSettings {
             mode: PlaybackMode::Loop,
+            volume: settings.music_volume(),
             ..default()
         },
     ));
@@ -258,85 +382,20 @@ fn setup_wave(
         return;
     };
 
-    camera_transform.translation = Vec3::ZERO;
+    camera_transform.translation = arena.keep_start.extend(0.0);
 
     // Build the arena
-    let texture_handle = asset_server.load("sprites/terrain.png");
-
-    let tilemap_entity = commands.spawn_empty().id();
-
-    let mut tile_storage = TileStorage::empty(AREA_SIZE.into());
-
-    let tilemap_id = TilemapId(tilemap_entity);
-
-    let mut rng = global_rng.fork_rng();
-
-    let mut tile_texture_index = |x, y| {
-        let point = UVec2::new(x, y);
-
-        if !arena.area.contains(point) { return TileTextureIndex(10); }
-
-        if x == arena.area.min.x {
-            if y == arena.area.min.y {
-                TileTextureIndex(12)
-            } else if y == arena.area.max.y {
-                TileTextureIndex(0)
-            } else {
-                TileTextureIndex(*[4, 8].iter().choose(&mut rng).unwrap())
-            }
-        } else if x == arena.area.max.x {
-            if y == arena.area.min.y {
-                TileTextureIndex(15)
-            } else if y == arena.area.max.y {
-                TileTextureIndex(3)
-            } else {
-                TileTextureIndex(*[7, 11].iter().choose(&mut rng).unwrap())
-            }
-        } else if y == arena.area.min.y {
-            TileTextureIndex(*[13, 14].iter().choose(&mut rng).unwrap())
-        } else if y == arena.area.max.y {
-            TileTextureIndex(*[1, 2].iter().choose(&mut rng).unwrap())
-        } else {
-            TileTextureIndex(*[5, 6, 9].iter().choose(&mut rng).unwrap())
-        }
-    };
-
-    for x in 0..AREA_SIZE.x {
-        for y in 0..AREA_SIZE.y {
-            let tile_pos = TilePos { x, y };
-            let tile_entity = commands
-                .spawn(TileBundle {
-                    position: tile_pos,
-                    texture_index: tile_texture_index(x, y),
-                    tilemap_id: tilemap_id,
-                    ..Default::default()
-                })
-                .id();
-            tile_storage.set(&tile_pos, tile_entity);
-        }
+    if settings.open_world {
+        commands.insert_resource(OpenWorld::new(game_controller.seed.wrapping_add(game_controller.wave_level as u64)));
+    } else {
+        let default_rules = AutotileRules::default();
+        let autotile_rules = asset_handles.autotile_rules("terrain").unwrap_or(&default_rules);
+
+        spawn_tilemap(&mut commands, &asset_handles, AREA_SIZE, Arena::tile_position(UVec2::ZERO), |point| {
+            (arena.tile(point.as_ivec2()), arena.texture_index(point, autotile_rules, &mut rng))
+        });
     }
 
-    let tile_size = TilemapTileSize {
-        x: TILE_SIZE,
-        y: TILE_SIZE,
-    };
-    let grid_size = tile_size.into();
-    let map_type = TilemapType::Square;
-
-    commands.entity(tilemap_entity).insert((
-        TilemapBundle {
-            grid_size,
-            map_type,
-            size: AREA_SIZE.into(),
-            storage: tile_storage,
-            texture: TilemapTexture::Single(texture_handle),
-            tile_size,
-            transform: get_tilemap_center_transform(&AREA_SIZE.into(), &grid_size, &map_type, -1.0),
-            ..default()
-        },
-        WaveTilemap,
-    ));
-
     // Setup rest of wave
 
     // UI
@@ -355,89 +414,85 @@ fn setup_wave(
             WaveUi,
         ))
         .with_children(|parent| {
-            parent
-                .spawn((
-                    Node {
-                        align_items: AlignItems::Center,
-                        display: Display::Flex,
-                        justify_content: JustifyContent::SpaceBetween,
-                        padding: UiRect::all(Val::Px(5.0)),
-                        ..default()
+            widgets::bar(parent).with_children(|parent| {
+                widgets::label(
+                    parent,
+                    &asset_handles,
+                    if *game_mode == GameMode::Endless {
+                        "Endless".to_string()
+                    } else {
+                        format!("Wave {}", game_controller.wave_level + 1)
                     },
-                    BackgroundColor(Color::BLACK),
-                ))
-                .with_children(|parent| {
-                    parent.spawn((
-                        Text::new(format!("Wave {}", game_controller.wave_level + 1)),
-                        TextColor(Color::WHITE),
-                        TextFont {
-                            font: asset_handles.font_map.get("default").unwrap().clone(),
-                            ..default()
-                        },
-                    ));
-
-                    parent.spawn((
-                        Text::new(""),
-                        TextColor(Color::WHITE),
-                        TextFont {
-                            font: asset_handles.font_map.get("default").unwrap().clone(),
-                            ..default()
-                        },
-                        WaveTimerUi,
-                    ));
-                });
-            parent
-                .spawn((
-                    Node {
-                        align_items: AlignItems::Center,
-                        display: Display::Flex,
-                        justify_content: JustifyContent::SpaceBetween,
-                        padding: UiRect::all(Val::Px(5.0)),
-                        ..default()
-                    },
-                    BackgroundColor(Color::BLACK),
-                ))
-                .with_children(|parent| {
-                    parent.spawn((
-                        HealthUi,
-                        Text::new(""),
-                        TextColor(Color::WHITE),
-                        TextFont {
-                            font: asset_handles.font_map.get("default").unwrap().clone(),
-                            ..default()
-                        },
-                    ));
-                });
+                );
+                widgets::label(parent, &asset_handles, "").insert(WaveTimerUi);
+            });
+            widgets::bar(parent).with_children(|parent| {
+                widgets::label(parent, &asset_handles, "").insert(HealthUi);
+                widgets::label(parent, &asset_handles, "").insert(GoldUi);
+            });
         });
 
     // Player
 
-    commands.spawn(Player::default());
+    commands.spawn((
+        Player {
+            direction: arena.keep_heading,
+            ..default()
+        },
+        Transform::from_translation(arena.keep_start.extend(0.0)),
+    ));
+
+    commands.insert_resource(arena);
 }
 
 fn spawn_enemies(
+    arena: Res<Arena>,
     mut commands: Commands,
     mut global_rng: GlobalEntropy<WyRand>,
+    player_query: Query<&Transform, With<Player>>,
+    tiles: TileCollision,
     time: Res<Time>,
     mut wave_controller: ResMut<WaveController>,
 ) {
 
     wave_controller.enemy_spawn_timer.tick(time.delta());
 
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+
     if wave_controller.enemy_spawn_timer.just_finished() {
-        let arena_real_boundary: Vec2 = (ARENA_SIZE - (ARENA_SIZE / 2)).as_vec2() * TILE_SIZE;
+        let keep = player_transform.translation.xy();
         let mut rng = global_rng.fork_rng();
 
         for _ in 0..wave_controller.enemy_spawn_amount {
+            // Not every zone fits every arena, so fall back on the others in turn.
+            let spawn_zones = wave_controller.spawn_zones.choose_multiple(&mut rng, wave_controller.spawn_zones.len()).collect::<Vec<_>>();
 
-            let x = rng.gen_range(0.0..arena_real_boundary.x);
-            let y = rng.gen_range(0.0..arena_real_boundary.y);
+            let Some(position) = spawn_zones.into_iter().find_map(|spawn_zone| spawn_zone.pick(&mut rng, &arena.playable_area, keep, |position| !tiles.is_solid(position))) else {
+                continue;
+            };
 
-            commands
+            let enemy_type = if rng.gen_bool(wave_controller.hunter_chance as f64) {
+                if rng.gen_bool(0.5) { EnemyType::Saboteur } else { EnemyType::Sniper }
+            } else if rng.gen_bool(wave_controller.brute_chance as f64) {
+                EnemyType::Brute
+            } else {
+                EnemyType::Normal
+            };
+
+            let mut enemy_commands = commands
                 .spawn((
-                    Enemy::default(),
-                    Transform::from_translation(Vec3::new(x, y, 0.0)),
+                    Enemy {
+                        flank_angle: rng.gen_range(-MAX_FLANK_ANGLE..MAX_FLANK_ANGLE),
+                        ..Enemy::new(enemy_type)
+                    },
+                    Transform::from_translation(position.extend(0.0)),
                 ));
+
+            if rng.gen_bool(wave_controller.elite_chance as f64) {
+                enemy_commands.insert(Elite::roll(&mut rng, wave_controller.elite_max_affixes));
+            }
         }
     }
 }
@@ -450,6 +505,11 @@ fn wave_timer_ui(
         return;
     };
 
+    if wave_controller.endless {
+        text.0 = format!("Time: {}", format_time(wave_controller.survived.elapsed_secs()));
+        return;
+    }
+
     text.0 = format!(
         "Time: {}/{}",
         wave_controller.wave_timer.remaining_secs() as u32,
@@ -462,27 +522,39 @@ pub struct WavePlugin;
 impl Plugin for WavePlugin {
     fn build(&self, app: &mut App) {
 
-        app.add_plugins((EnemyPlugin, PlayerPlugin));
+        app.add_plugins((
+            AnnouncementPlugin,
+            ArenaShiftPlugin,
+            ElitePlugin,
+            EnemyPlugin,
+            HazardPlugin,
+            HunterPlugin,
+            KnockbackPlugin,
+            LootPlugin,
+            OpenWorldPlugin,
+            PlayerPlugin,
+            ProjectilePlugin,
+            PropPlugin,
+            SpawnZonePlugin,
+            StatusEffectPlugin,
+        ));
         app.add_sub_state::<WaveState>();
         app.add_systems(OnEnter(GameState::Wave), setup_wave);
-        app.add_systems(OnEnter(WaveState::Complete), setup_finished);
-        app.add_systems(OnExit(WaveState::Complete), destroy_finished);
-        app.add_systems(OnEnter(WaveState::GameOver), setup_game_over);
-        app.add_systems(OnExit(WaveState::GameOver), destroy_game_over);
-        app.add_systems(OnEnter(WaveState::Preparation), setup_preparation);
-        app.add_systems(OnExit(WaveState::Preparation), destroy_preparation);
+        app.add_systems(OnEnter(WaveState::Complete), announce_finished);
+        app.add_systems(OnEnter(WaveState::GameOver), (announce_game_over, record_endless_time));
+        app.add_systems(OnEnter(WaveState::Preparation), announce_preparation);
         app.add_systems(OnExit(GameState::Wave), destroy_wave);
         app.add_systems(
             Update,
             (
-                (boundary_collision, spawn_enemies).in_set(WaveRunningSet),
-                (health_ui, wave_timer_tick, wave_timer_ui),
-                prepare.run_if(in_state(WaveState::Preparation)),
+                (boundary_collision, spawn_enemies)
+                    .in_set(PausableSet)
+                    .in_set(WaveRunningSet),
+                wave_timer_tick.in_set(PausableSet),
+                (gold_ui, health_ui, wave_timer_ui),
             ).run_if(in_state(GameState::Wave)),
         );
 
         app.configure_sets(Update, WaveRunningSet.run_if(in_state(WaveState::Running)));
-
-        app.init_resource::<Arena>();
     }
 }
@@ -0,0 +1,396 @@
+use std::ops::RangeInclusive;
+
+use bevy::{
+    math::bounding::*,
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use bevy_ecs_tilemap::prelude::*;
+use bevy_prng::WyRand;
+use rand::{Rng, seq::SliceRandom};
+use rand_core::SeedableRng;
+
+use crate::arena_map::{Hazard, Prop};
+use crate::asset_handles::AssetHandles;
+use crate::autotile::AutotileRules;
+use crate::colors::{GOLD, LIME_GREEN};
+use crate::game::{game_controller::GameController, game_sets::PausableSet, game_state::GameState};
+use crate::health::Health;
+
+use super::announcement::Announcement;
+use super::arena::{Arena, ArenaTile, LOOP_SPACE_RADIUS, TILE_SIZE};
+use super::player::Player;
+use super::prop::RUBBLE;
+use super::spawn_tilemap;
+use super::wave_sets::WaveRunningSet;
+
+const CACHE_GOLD: u32 = 5;
+const CACHE_GOLD_PER_LEVEL: u32 = 2;
+/// The size of each chunk of the world, in tiles.
+const CHUNK_SIZE: UVec2 = UVec2::new(16, 16);
+const HAZARD_PATCH_CHANCE: f64 = 0.35;
+const HAZARD_PATCH_SIZE: RangeInclusive<u32> = 2..=4;
+const LANDMARK_CHANCE: f64 = 0.15;
+/// Tiles around a landmark that are always left clear, so it can be driven over.
+const LANDMARK_CLEARING: i32 = 2;
+const LANDMARK_SIZE: f32 = 14.0;
+/// Chunks this many chunks or fewer from the keep's are kept loaded.
+const LOAD_DISTANCE: i32 = 2;
+const POINTER_DISTANCE: f32 = 28.0;
+const POINTER_FRAME: usize = 1;
+const PROP_CHANCE: f64 = 0.03;
+const ROCK_CHANCE: f64 = 0.04;
+const SHRINE_HEAL: u32 = 3;
+/// Enemies spawn within this distance of the keep, roughly the edges of the screen.
+const SPAWN_AREA_HALF_SIZE: Vec2 = Vec2::new(360.0, 220.0);
+/// Chunks further than this many chunks from the keep's are despawned.
+const UNLOAD_DISTANCE: i32 = 3;
+
+#[derive(Clone, Copy)]
+enum LandmarkKind {
+    /// Pays out gold.
+    Cache,
+    /// Repairs the keep.
+    Shrine,
+}
+
+/// Something out in the world worth driving over.
+#[derive(Component)]
+#[require(Sprite, Transform, Visibility)]
+struct Landmark {
+    chunk: IVec2,
+    kind: LandmarkKind,
+}
+
+/// Points from the keep towards the nearest landmark.
+#[derive(Component)]
+#[require(Sprite, Transform, Visibility)]
+struct LandmarkPointer;
+
+/// An endless world, streamed in chunk by chunk around the keep as it travels. The same seed
+/// always lays out the same world.
+#[derive(Resource)]
+pub struct OpenWorld {
+    chunks: HashMap<IVec2, Entity>,
+    /// Chunks whose landmark has been claimed, so it doesn't come back when they reload.
+    claimed: HashSet<IVec2>,
+    seed: u64,
+    /// Tiles in each chunk whose prop has been smashed, so it doesn't come back either.
+    smashed: HashMap<IVec2, HashSet<UVec2>>,
+}
+
+impl OpenWorld {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            chunks: HashMap::default(),
+            claimed: HashSet::default(),
+            seed,
+            smashed: HashMap::default(),
+        }
+    }
+
+    /// Remembers that the prop on the tile `position` lies in was smashed.
+    pub fn smash(&mut self, position: Vec2) {
+        let chunk = Self::chunk_at(position);
+        let point = ((position - Self::chunk_origin(chunk)) / TILE_SIZE)
+            .round()
+            .as_uvec2();
+
+        self.smashed.entry(chunk).or_default().insert(point);
+    }
+
+    fn chunk_at(position: Vec2) -> IVec2 {
+        ((position + TILE_SIZE / 2.0) / (CHUNK_SIZE.as_vec2() * TILE_SIZE))
+            .floor()
+            .as_ivec2()
+    }
+
+    /// The middle of the bottom left tile of `chunk`, in world space.
+    fn chunk_origin(chunk: IVec2) -> Vec2 {
+        (chunk * CHUNK_SIZE.as_ivec2()).as_vec2() * TILE_SIZE
+    }
+
+    fn chunk_rng(&self, chunk: IVec2) -> WyRand {
+        let hash = (chunk.x as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15)
+            ^ (chunk.y as u64).wrapping_mul(0xc2b2_ae3d_27d4_eb4f);
+
+        WyRand::seed_from_u64(self.seed ^ hash)
+    }
+
+    /// Scatters rocks, props and maybe a patch of hazard over `chunk`, leaving room for its
+    /// landmark if it has one and for the keep to loop around at the start.
+    fn generate_chunk(
+        &self,
+        chunk: IVec2,
+        rng: &mut impl Rng,
+    ) -> (Vec<ArenaTile>, Option<LandmarkKind>) {
+        let index = |point: UVec2| (point.y * CHUNK_SIZE.x + point.x) as usize;
+        let mut tiles = (0..CHUNK_SIZE.element_product())
+            .map(|_| {
+                if rng.gen_bool(ROCK_CHANCE) {
+                    ArenaTile::Obstacle
+                } else if rng.gen_bool(PROP_CHANCE) {
+                    ArenaTile::Prop(*Prop::ALL.choose(rng).unwrap())
+                } else {
+                    ArenaTile::Floor
+                }
+            })
+            .collect::<Vec<_>>();
+
+        if rng.gen_bool(HAZARD_PATCH_CHANCE) {
+            let hazard = *Hazard::ALL.choose(rng).unwrap();
+            let size = UVec2::new(
+                rng.gen_range(HAZARD_PATCH_SIZE),
+                rng.gen_range(HAZARD_PATCH_SIZE),
+            );
+            let min = UVec2::new(
+                rng.gen_range(0..=CHUNK_SIZE.x - size.x),
+                rng.gen_range(0..=CHUNK_SIZE.y - size.y),
+            );
+
+            for x in min.x..min.x + size.x {
+                for y in min.y..min.y + size.y {
+                    tiles[index(UVec2::new(x, y))] = ArenaTile::Hazard(hazard);
+                }
+            }
+        }
+
+        let landmark = (chunk != IVec2::ZERO
+            && !self.claimed.contains(&chunk)
+            && rng.gen_bool(LANDMARK_CHANCE))
+        .then(|| {
+            *[LandmarkKind::Cache, LandmarkKind::Shrine]
+                .choose(rng)
+                .unwrap()
+        });
+
+        for x in 0..CHUNK_SIZE.x {
+            for y in 0..CHUNK_SIZE.y {
+                let point = UVec2::new(x, y);
+                let from_middle = (point.as_ivec2() - (CHUNK_SIZE / 2).as_ivec2()).abs();
+                let from_start = Self::chunk_origin(chunk) + point.as_vec2() * TILE_SIZE;
+
+                if self.is_smashed(chunk, point)
+                    || (landmark.is_some() && from_middle.max_element() <= LANDMARK_CLEARING)
+                    || from_start.length() / TILE_SIZE <= LOOP_SPACE_RADIUS + 1.0
+                {
+                    tiles[index(point)] = ArenaTile::Floor;
+                }
+            }
+        }
+
+        (tiles, landmark)
+    }
+
+    fn is_smashed(&self, chunk: IVec2, point: UVec2) -> bool {
+        self.smashed
+            .get(&chunk)
+            .is_some_and(|smashed| smashed.contains(&point))
+    }
+
+    fn spawn_chunk(
+        &mut self,
+        asset_handles: &AssetHandles,
+        autotile_rules: &AutotileRules,
+        chunk: IVec2,
+        commands: &mut Commands,
+    ) {
+        let mut rng = self.chunk_rng(chunk);
+        let (tiles, landmark) = self.generate_chunk(chunk, &mut rng);
+
+        let chunk_entity = spawn_tilemap(
+            commands,
+            asset_handles,
+            CHUNK_SIZE,
+            Self::chunk_origin(chunk),
+            |point| {
+                let tile = tiles[(point.y * CHUNK_SIZE.x + point.x) as usize];
+                if self.is_smashed(chunk, point) {
+                    return (tile, TileTextureIndex(RUBBLE));
+                }
+
+                // Nothing in the open world is walled in, so floor never gets edges.
+                let texture = tile.texture().unwrap_or_else(|| {
+                    TileTextureIndex(autotile_rules.texture_index(
+                        point.as_ivec2(),
+                        |_| false,
+                        &mut rng,
+                    ))
+                });
+
+                (tile, texture)
+            },
+        );
+
+        if let Some(kind) = landmark {
+            let color = match kind {
+                LandmarkKind::Cache => GOLD,
+                LandmarkKind::Shrine => LIME_GREEN,
+            };
+
+            commands.entity(chunk_entity).with_child((
+                Landmark { chunk, kind },
+                Sprite::from_color(color, Vec2::splat(LANDMARK_SIZE)),
+                Transform::from_translation(((CHUNK_SIZE / 2).as_vec2() * TILE_SIZE).extend(1.25)),
+            ));
+        }
+
+        self.chunks.insert(chunk, chunk_entity);
+    }
+}
+
+fn add_landmark_pointer(
+    asset_handles: Res<AssetHandles>,
+    mut commands: Commands,
+    query: Query<Entity, Added<Player>>,
+) {
+    for player_entity in query.iter() {
+        let mut sprite = Sprite::from_atlas_image(
+            asset_handles.image("weapon"),
+            asset_handles.texture_atlas("weapon", POINTER_FRAME),
+        );
+
+        sprite.color = GOLD;
+
+        commands
+            .entity(player_entity)
+            .with_child((LandmarkPointer, sprite, Visibility::Hidden));
+    }
+}
+
+/// Driving over a landmark claims it for good.
+fn claim_landmarks(
+    mut announcement_events: EventWriter<Announcement>,
+    mut commands: Commands,
+    mut game_controller: ResMut<GameController>,
+    landmark_query: Query<(Entity, &Landmark, &GlobalTransform)>,
+    mut open_world: ResMut<OpenWorld>,
+    mut player_query: Query<(&Player, &mut Health, &Transform)>,
+) {
+    let Ok((player, mut health, player_transform)) = player_query.get_single_mut() else {
+        return;
+    };
+
+    let volume = player.volume(player_transform);
+
+    for (landmark_entity, landmark, landmark_transform) in landmark_query.iter() {
+        let landmark_volume =
+            BoundingCircle::new(landmark_transform.translation().xy(), LANDMARK_SIZE / 2.0);
+
+        if !volume.intersects(&landmark_volume) {
+            continue;
+        }
+
+        let message = match landmark.kind {
+            LandmarkKind::Cache => {
+                let gold = CACHE_GOLD + game_controller.wave_level * CACHE_GOLD_PER_LEVEL;
+
+                game_controller.gold += gold;
+                format!("+{} gold", gold)
+            }
+            LandmarkKind::Shrine => {
+                health.current = (health.current + SHRINE_HEAL).min(health.max);
+                "Keep repaired".to_string()
+            }
+        };
+
+        announcement_events.send(Announcement::new(message));
+        open_world.claimed.insert(landmark.chunk);
+        commands.entity(landmark_entity).despawn_recursive();
+    }
+}
+
+fn point_to_landmark(
+    landmark_query: Query<&GlobalTransform, With<Landmark>>,
+    mut pointer_query: Query<(&mut Transform, &mut Visibility), With<LandmarkPointer>>,
+    player_query: Query<&Transform, (With<Player>, Without<LandmarkPointer>)>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+
+    let Ok((mut pointer_transform, mut visibility)) = pointer_query.get_single_mut() else {
+        return;
+    };
+
+    let keep = player_transform.translation.xy();
+    let nearest = landmark_query
+        .iter()
+        .map(|transform| transform.translation().xy() - keep)
+        .min_by(|a, b| a.length_squared().total_cmp(&b.length_squared()));
+
+    let Some(direction) = nearest.and_then(Vec2::try_normalize) else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+
+    *visibility = Visibility::Inherited;
+    pointer_transform.translation = (direction * POINTER_DISTANCE).extend(1.0);
+    pointer_transform.rotation = Quat::from_rotation_arc(Vec3::Y, direction.extend(0.0));
+}
+
+/// Loads the chunks around the keep and unloads those it has left behind. The arena's playable
+/// area follows the keep, so enemies spawn around it wherever it goes.
+fn stream_chunks(
+    mut arena: ResMut<Arena>,
+    asset_handles: Res<AssetHandles>,
+    mut commands: Commands,
+    mut open_world: ResMut<OpenWorld>,
+    player_query: Query<&Transform, With<Player>>,
+    tilemap_query: Query<&TileStorage>,
+) {
+    let keep = player_query
+        .get_single()
+        .map_or(Vec2::ZERO, |transform| transform.translation.xy());
+    let centre = OpenWorld::chunk_at(keep);
+
+    open_world.chunks.retain(|chunk, chunk_entity| {
+        if (*chunk - centre).abs().max_element() <= UNLOAD_DISTANCE {
+            return true;
+        }
+
+        // Tiles aren't children of their tilemap, so go through its storage
+        if let Ok(tile_storage) = tilemap_query.get(*chunk_entity) {
+            for tile_entity in tile_storage.iter().flatten() {
+                commands.entity(*tile_entity).despawn();
+            }
+        }
+
+        commands.entity(*chunk_entity).despawn_recursive();
+        false
+    });
+
+    let default_rules = AutotileRules::default();
+    let autotile_rules = asset_handles
+        .autotile_rules("terrain")
+        .unwrap_or(&default_rules);
+
+    for x in -LOAD_DISTANCE..=LOAD_DISTANCE {
+        for y in -LOAD_DISTANCE..=LOAD_DISTANCE {
+            let chunk = centre + IVec2::new(x, y);
+
+            if !open_world.chunks.contains_key(&chunk) {
+                open_world.spawn_chunk(&asset_handles, autotile_rules, chunk, &mut commands);
+            }
+        }
+    }
+
+    arena.playable_area = Aabb2d::new(keep, SPAWN_AREA_HALF_SIZE);
+}
+
+pub struct OpenWorldPlugin;
+
+impl Plugin for OpenWorldPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                (add_landmark_pointer, stream_chunks).in_set(PausableSet),
+                (claim_landmarks, point_to_landmark)
+                    .in_set(PausableSet)
+                    .in_set(WaveRunningSet),
+            )
+                .run_if(in_state(GameState::Wave).and(resource_exists::<OpenWorld>)),
+        );
+    }
+}
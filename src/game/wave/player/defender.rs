@@ -1,32 +1,52 @@
-use bevy::{math::bounding::{BoundingCircle, IntersectsVolume}, prelude::*};
+use bevy::prelude::*;
 
-use crate::{asset_handles::AssetHandles, game::wave::enemy::{Enemy, EnemyState}, health::Health};
+use crate::{asset_handles::AssetHandles, colors::DARK_GRAY, game::{game_controller::DEFENDER_HEALTH, game_sets::PausableSet, wave::{enemy::{Enemy, EnemyState}, wave_sets::WaveRunningSet}}, health::{DamageType, Died, Health, HealthState}};
+use crate::game::wave::announcement::{Announcement, AnnouncementStyle};
+use crate::game::wave::projectile::{Flight, Projectile};
+use crate::game::wave::status_effect::{StatusEffect, StatusEffectKind};
 
 const DEFAULT_ARROW_DAMAGE: u32 = 2;
-const DEFAULT_ARROW_SPEED: f32 = 200.0;
+const ELEMENTAL_ARROW_DAMAGE: u32 = 1;
+const BOMB_KNOCKBACK: f32 = 160.0;
+const BOMB_SPLASH_RADIUS: f32 = 24.0;
+const CHAKRAM_RICOCHETS: u32 = 3;
+const PIERCING_BOLT_PIERCE: u32 = 3;
+
+/// Where each member of the crew stands on the keep, in order.
+pub const CREW_SLOTS: [Vec2; 4] = [Vec2::new(-6.0, 6.0), Vec2::new(6.0, -6.0), Vec2::new(6.0, 6.0), Vec2::new(-6.0, -6.0)];
 
 pub enum DefenderType {
     Archer,
 }
 
 #[derive(Component)]
-#[require(Transform, Visibility)]
+#[require(Health(|| DEFENDER_HEALTH), Sprite, Transform, Visibility)]
 pub struct Defender {
     pub action_timer: Timer,
+    /// Which member of `GameController::crew` this defender is.
+    pub crew_index: usize,
     pub defender_type: DefenderType,
+    pub weapon_type: WeaponType,
 }
 
 impl Default for Defender {
     fn default() -> Self {
         Self {
             action_timer: Timer::from_seconds(1.0, TimerMode::Once),
+            crew_index: 0,
             defender_type: DefenderType::Archer,
+            weapon_type: WeaponType::default(),
         }
     }
 }
 
-fn defender_action(mut commands: Commands, enemy_query: Query<(&Enemy, Entity, &Transform)>, mut defender_query: Query<(&mut Defender, &Transform)>, time: Res<Time>) {
-    for (mut defender, defender_transform) in defender_query.iter_mut() {
+/// Defenders with no health left are knocked out, and stop shooting until revived.
+fn defender_action(mut commands: Commands, enemy_query: Query<(&Enemy, Entity, &Transform)>, mut defender_query: Query<(&mut Defender, Entity, &GlobalTransform, &Health)>, time: Res<Time>) {
+    for (mut defender, defender_entity, defender_transform, health) in defender_query.iter_mut() {
+        if health.state == HealthState::Dead {
+            continue;
+        }
+
         defender.action_timer.tick(time.delta());
 
         if defender.action_timer.finished() {
@@ -40,7 +60,7 @@ fn defender_action(mut commands: Commands, enemy_query: Query<(&Enemy, Entity, &
                             continue;
                         }
 
-                        let new_ds = enemy_transform.translation.distance_squared(defender_transform.translation);
+                        let new_ds = enemy_transform.translation.distance_squared(defender_transform.translation());
 
                         let Some(ds_value) = ds else {
                             ds = Some(new_ds);
@@ -61,16 +81,10 @@ fn defender_action(mut commands: Commands, enemy_query: Query<(&Enemy, Entity, &
 
                     commands.spawn((
                         Transform {
-                            translation: defender_transform.translation.clone(),
-                            ..default()
-                        },
-                        Weapon {
-                            damage: match defender.defender_type {
-                                DefenderType::Archer => DEFAULT_ARROW_DAMAGE,
-                            },
-                            target: target,
+                            translation: defender_transform.translation(),
                             ..default()
                         },
+                        defender.weapon_type.projectile(Some(defender_entity), target),
                     ));
                 }
             }
@@ -80,97 +94,125 @@ fn defender_action(mut commands: Commands, enemy_query: Query<(&Enemy, Entity, &
     }
 }
 
-#[derive(Default)]
-pub enum WeaponType {
-    #[default]
-    Arrow,
-}
-
-#[derive(Component)]
-#[require(Sprite)]
-pub struct Weapon {
-    damage: u32,
-    death_timer: Timer,
-    direction: Vec2,
-    speed: f32,
-    target: Option<Entity>,
-    weapon_type: WeaponType,
+fn defender_appearance(mut query: Query<(&Defender, &Health, &mut Sprite), Changed<Health>>) {
+    for (defender, health, mut sprite) in query.iter_mut() {
+        sprite.color = match health.state {
+            HealthState::Alive => defender.weapon_type.status_effect().map_or(Color::WHITE, |effect| effect.kind.tint()),
+            HealthState::Dead => DARK_GRAY,
+        };
+    }
 }
 
-impl Default for Weapon {
-    fn default() -> Self {
-        Self {
-            damage: 0,
-            death_timer: Timer::from_seconds(20.0, TimerMode::Once),
-            direction: Vec2::ZERO,
-            speed: DEFAULT_ARROW_SPEED,
-            target: None,
-            weapon_type: WeaponType::default(),
+fn defender_down(mut announcement_events: EventWriter<Announcement>, mut died_events: EventReader<Died>, query: Query<(), With<Defender>>) {
+    for event in died_events.read() {
+        if query.contains(event.entity) {
+            announcement_events.send(Announcement::new("Defender down!").with_duration(1.0).with_style(AnnouncementStyle::Warning));
         }
     }
 }
 
-fn initialize_weapon(asset_handles: Res<AssetHandles>, mut query: Query<(&mut Sprite, &Weapon), Added<Weapon>>) {
-    for (mut sprite, weapon) in query.iter_mut() {
-        sprite.image = asset_handles.image_map.get("weapon").unwrap().clone();
-        sprite.texture_atlas = Some(TextureAtlas {
-            index: match weapon.weapon_type {
-                WeaponType::Arrow => 0,
-            },
-            layout: asset_handles
-                .texture_atlas_layout_map
-                .get("weapon")
-                .unwrap()
-                .clone(),
-        });
+/// Defenders show the weapon they are holding.
+fn initialize_defender(asset_handles: Res<AssetHandles>, mut query: Query<(&Defender, &mut Sprite), Added<Defender>>) {
+    for (defender, mut sprite) in query.iter_mut() {
+        sprite.image = asset_handles.image("weapon");
+        sprite.texture_atlas = Some(asset_handles.texture_atlas("weapon", defender.weapon_type.frame()));
     }
 }
 
-fn weapon_cancellation(mut commands: Commands, mut query: Query<(Entity, &mut Weapon)>, time: Res<Time>) {
-    for (entity, mut weapon) in query.iter_mut() {
-        weapon.death_timer.tick(time.delta());
-
-        if weapon.death_timer.just_finished() {
-            commands.entity(entity).despawn();
-        }
-    }
+#[derive(Clone, Copy, Default)]
+pub enum WeaponType {
+    #[default]
+    Arrow,
+    FireArrow,
+    FrostArrow,
+    PiercingBolt,
+    PoisonArrow,
+    StunBolt,
+    Bomb,
+    Chakram,
 }
 
-fn weapon_hit(mut commands: Commands, mut enemy_query: Query<(&Enemy, &mut Health, &Transform)>, weapon_query: Query<(Entity, &Transform, &Weapon)>) {
-    for (weapon_entity, weapon_transform, weapon) in weapon_query.iter() {
-        let weapon_volume = BoundingCircle::new(weapon_transform.translation.xy(), 4.0);
-
-        for (enemy, mut enemy_health, enemy_transform) in enemy_query.iter_mut() {
-            if !enemy.volume(enemy_transform).intersects(&weapon_volume) {
-                continue;
-            }
-
-            commands.entity(weapon_entity).despawn();
+impl WeaponType {
+    /// The weapons an archer is handed out, one per wave in turn.
+    const ROTATION: [WeaponType; 8] = [
+        WeaponType::Arrow,
+        WeaponType::FireArrow,
+        WeaponType::FrostArrow,
+        WeaponType::PoisonArrow,
+        WeaponType::StunBolt,
+        WeaponType::PiercingBolt,
+        WeaponType::Bomb,
+        WeaponType::Chakram,
+    ];
+
+    pub fn for_wave(wave_level: u32) -> Self {
+        Self::ROTATION[wave_level as usize % Self::ROTATION.len()]
+    }
 
-            enemy_health.current = enemy_health.current.saturating_sub(weapon.damage);
+    fn damage(&self) -> u32 {
+        match self {
+            WeaponType::Arrow | WeaponType::Chakram | WeaponType::PiercingBolt => DEFAULT_ARROW_DAMAGE,
+            _ => ELEMENTAL_ARROW_DAMAGE,
         }
     }
-}
 
-fn weapon_movement(mut commands: Commands, enemy_query: Query<&Transform, With<Enemy>>, time: Res<Time>, mut weapon_query: Query<(Entity, &mut Transform, &mut Weapon), Without<Enemy>>) {
-    for (weapon_entity, mut weapon_transform, mut weapon) in weapon_query.iter_mut() {
+    fn damage_type(&self) -> DamageType {
+        match self {
+            WeaponType::Arrow | WeaponType::FrostArrow | WeaponType::PiercingBolt | WeaponType::PoisonArrow => DamageType::Pierce,
+            WeaponType::Bomb | WeaponType::FireArrow => DamageType::Fire,
+            WeaponType::Chakram | WeaponType::StunBolt => DamageType::Blunt,
+        }
+    }
 
-        weapon.direction = match weapon.target {
-            Some(enemy_entity) => {
-                let Ok(enemy_transform) = enemy_query.get(enemy_entity) else {
-                    commands.entity(weapon_entity).despawn();
-                    continue;
-                };
+    /// The frame of the weapon sprite sheet: arrow, bolt, bomb and chakram.
+    fn frame(&self) -> usize {
+        match self {
+            WeaponType::Arrow | WeaponType::FireArrow | WeaponType::FrostArrow | WeaponType::PoisonArrow => 0,
+            WeaponType::PiercingBolt | WeaponType::StunBolt => 1,
+            WeaponType::Bomb => 2,
+            WeaponType::Chakram => 3,
+        }
+    }
 
-                (enemy_transform.translation - weapon_transform.translation).normalize().truncate()
-            },
-            None => weapon.direction
+    fn projectile(&self, owner: Option<Entity>, target: Option<Entity>) -> Projectile {
+        let projectile = Projectile {
+            damage: self.damage(),
+            damage_type: self.damage_type(),
+            frame: self.frame(),
+            owner,
+            status_effect: self.status_effect(),
+            target,
+            ..default()
         };
 
-        let translation = weapon.direction * weapon.speed * time.delta_secs();
+        match self {
+            WeaponType::Bomb => Projectile {
+                flight: Flight::Straight,
+                knockback: BOMB_KNOCKBACK,
+                splash_radius: BOMB_SPLASH_RADIUS,
+                ..projectile
+            },
+            WeaponType::Chakram => Projectile {
+                ricochet: CHAKRAM_RICOCHETS,
+                ..projectile
+            },
+            WeaponType::PiercingBolt => Projectile {
+                flight: Flight::Straight,
+                pierce: PIERCING_BOLT_PIERCE,
+                ..projectile
+            },
+            _ => projectile,
+        }
+    }
 
-        weapon_transform.translation += translation.extend(0.0);
-        weapon_transform.rotation = Quat::from_rotation_arc(Vec3::Y, weapon.direction.extend(0.0));
+    fn status_effect(&self) -> Option<StatusEffect> {
+        match self {
+            WeaponType::Arrow | WeaponType::Bomb | WeaponType::Chakram | WeaponType::PiercingBolt => None,
+            WeaponType::FireArrow => Some(StatusEffect::new(StatusEffectKind::Burn, 3.0, 1.0)),
+            WeaponType::FrostArrow => Some(StatusEffect::new(StatusEffectKind::Slow, 2.0, 0.5)),
+            WeaponType::PoisonArrow => Some(StatusEffect::new(StatusEffectKind::Poison, 4.0, 1.0)),
+            WeaponType::StunBolt => Some(StatusEffect::new(StatusEffectKind::Stun, 0.75, 0.0)),
+        }
     }
 }
 
@@ -178,6 +220,11 @@ pub struct DefenderPlugin;
 
 impl Plugin for DefenderPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, (defender_action, initialize_weapon, weapon_cancellation, weapon_hit, weapon_movement));
+        app.add_systems(
+            Update,
+            (defender_action, defender_appearance.after(initialize_defender), defender_down, initialize_defender)
+                .in_set(PausableSet)
+                .in_set(WaveRunningSet),
+        );
     }
 }
\ No newline at end of file
@@ -1,22 +1,31 @@
-mod defender;
+pub mod defender;
 
 use bevy::{math::bounding::*, prelude::*};
-use defender::{Defender, DefenderPlugin};
+use defender::{CREW_SLOTS, Defender, DefenderPlugin, WeaponType};
 use leafwing_input_manager::prelude::*;
 
 use crate::{
     action::{default_input_map, Action},
+    animation_controller::{AnimationController, AnimationParameters},
     asset_handles::AssetHandles,
-    game::{game_sets::PausableSet, wave::wave_state::WaveState},
-    health::Health, simple_animations::SimpleAnimation,
+    game::{game_controller::GameController, game_sets::PausableSet, wave::wave_state::WaveState},
+    health::{Died, Health, HealthState},
+    simple_animations::{AnimationFinished, SimpleAnimation},
 };
 
-use super::wave_sets::WaveRunningSet;
+use super::{
+    announcement::{Announcement, AnnouncementStyle},
+    hazard::Footing,
+    status_effect::StatusEffects,
+    wave_sets::WaveRunningSet,
+};
 
-const DEATH_RATE: f32 = 1.0;
 const DEFAULT_DIRECTION: Vec2 = Vec2::Y;
+const DEFAULT_RAM_DAMAGE: u32 = 1;
+const DEFAULT_RAM_FORCE: f32 = 150.0;
 const DEFAULT_SPEED: f32 = 120.0;
 const INVINCIBILITY_RATE: f32 = 0.25;
+const LOW_HEALTH_RATIO: f32 = 0.3;
 pub const PLAYER_SIZE: f32 = 16.0;
 const TURN_RATE: f32 = 0.03;
 
@@ -29,23 +38,38 @@ pub enum PlayerState {
     Normal,
 }
 
+impl PlayerState {
+    /// The state name the keep's animation controller reacts to.
+    fn animation_state(&self) -> &'static str {
+        match self {
+            PlayerState::Dead => "dead",
+            PlayerState::Invincible => "invincible",
+            PlayerState::Normal => "normal",
+        }
+    }
+}
+
 #[derive(Component)]
-#[require(ActionState<Action>, Health(|| 10), InputMap::<Action>(default_input_map), SimpleAnimation,  Sprite, Transform, Visibility)]
+#[require(ActionState<Action>, AnimationController(|| AnimationController::new("keep")), Footing, Health(|| 10), InputMap::<Action>(default_input_map), Sprite, StatusEffects, Transform, Visibility)]
 pub struct Player {
-    pub death_timer: Timer,
     pub direction: Vec2,
     pub invincibility_timer: Timer,
     pub player_state: PlayerState,
+    /// Trample damage dealt to each enemy the keep drives into.
+    pub ram_damage: u32,
+    /// How hard enemies the keep drives into are shoved aside.
+    pub ram_force: f32,
     pub speed: f32,
 }
 
 impl Default for Player {
     fn default() -> Self {
         Self {
-            death_timer: Timer::from_seconds(DEATH_RATE, TimerMode::Once),
             direction: DEFAULT_DIRECTION,
             invincibility_timer: Timer::from_seconds(INVINCIBILITY_RATE, TimerMode::Once),
             player_state: PlayerState::Normal,
+            ram_damage: DEFAULT_RAM_DAMAGE,
+            ram_force: DEFAULT_RAM_FORCE,
             speed: DEFAULT_SPEED,
         }
     }
@@ -66,9 +90,8 @@ fn destroy_player(mut commands: Commands, query: Query<Entity, With<Player>>) {
 }
 
 fn follow_player(
-    mut camera_query: Query<&mut Transform, (With<Camera>, Without<Defender>, Without<Player>)>,
-    mut defender_query: Query<&mut Transform, (With<Defender>, Without<Camera>, Without<Player>)>,
-    player_query: Query<&Transform, (With<Player>, Without<Camera>, Without<Defender>)>,
+    mut camera_query: Query<&mut Transform, (With<Camera>, Without<Player>)>,
+    player_query: Query<&Transform, (With<Player>, Without<Camera>)>,
 ) {
     let Ok(player_transform) = player_query.get_single() else {
         return;
@@ -78,42 +101,78 @@ fn follow_player(
         camera_transform.translation.x = player_transform.translation.x;
         camera_transform.translation.y = player_transform.translation.y;
     }
-
-    for mut defender_transform in defender_query.iter_mut() {
-        defender_transform.translation.x = player_transform.translation.x;
-        defender_transform.translation.y = player_transform.translation.y;
-    }
 }
 
 fn initialize_player(
     asset_handles: Res<AssetHandles>,
     mut commands: Commands,
-    mut query: Query<(Entity, &mut SimpleAnimation, &mut Sprite), Added<Player>>,
+    game_controller: Res<GameController>,
+    mut query: Query<(Entity, &mut Sprite), Added<Player>>,
 ) {
-    let Ok((player_entity, mut player_animation, mut player_sprite)) = query.get_single_mut() else {
+    let Ok((player_entity, mut player_sprite)) = query.get_single_mut() else {
         return;
     };
 
     // Set the player's sprite
-    player_animation.frames = vec![0, 1, 0, 2];
-    player_sprite.image = asset_handles.image_map.get("player").unwrap().clone();
-    player_sprite.texture_atlas = Some(TextureAtlas {
-        index: 0,
-        layout: asset_handles
-            .texture_atlas_layout_map
-            .get("player")
-            .unwrap()
-            .clone(),
-    });
+    player_sprite.image = asset_handles.image("player");
+    player_sprite.texture_atlas = Some(asset_handles.texture_atlas("player", 0));
+
+    // Man the keep with the crew, whose weapons change from wave to wave. Knocked out
+    // defenders still take their place, but sit the wave out.
+    for (crew_index, member) in game_controller.crew.iter().enumerate().take(CREW_SLOTS.len()) {
+        let health = if member.down {
+            Health {
+                current: 0,
+                state: HealthState::Dead,
+                ..Health::from(member.max_health)
+            }
+        } else {
+            Health {
+                current: member.health,
+                ..Health::from(member.max_health)
+            }
+        };
+
+        let defender_entity = commands
+            .spawn((
+                Defender {
+                    crew_index,
+                    weapon_type: WeaponType::for_wave(game_controller.wave_level + crew_index as u32),
+                    ..default()
+                },
+                health,
+                Transform::from_translation(CREW_SLOTS[crew_index].extend(1.0)),
+            ))
+            .id();
+
+        commands.entity(player_entity).add_child(defender_entity);
+    }
+}
+
+fn low_health_warning(
+    mut announcement_events: EventWriter<Announcement>,
+    query: Query<&Health, (Changed<Health>, With<Player>)>,
+    mut warned: Local<bool>,
+) {
+    let Ok(health) = query.get_single() else {
+        return;
+    };
 
-    // Start the player with an archers
-    let archer_entity = commands.spawn(Defender::default()).id();
+    let low = health.current > 0 && (health.current as f32) <= health.max as f32 * LOW_HEALTH_RATIO;
+
+    if low && !*warned {
+        announcement_events.send(
+            Announcement::new("Low HP!")
+                .with_duration(1.0)
+                .with_style(AnnouncementStyle::Warning),
+        );
+    }
 
-    commands.entity(player_entity).add_child(archer_entity);
+    *warned = low;
 }
 
-fn move_player(mut query: Query<(&Player, &mut Transform)>, time: Res<Time>) {
-    let Ok((player, mut transform)) = query.get_single_mut() else {
+fn move_player(mut query: Query<(&Player, &Footing, &StatusEffects, &mut Transform)>, time: Res<Time>) {
+    let Ok((player, footing, status_effects, mut transform)) = query.get_single_mut() else {
         return;
     };
 
@@ -121,17 +180,28 @@ fn move_player(mut query: Query<(&Player, &mut Transform)>, time: Res<Time>) {
         return;
     }
 
-    let translation = player.direction * player.speed * time.delta_secs();
+    let speed = player.speed * status_effects.speed_multiplier() * footing.speed_multiplier();
+    let translation = player.direction * speed * time.delta_secs();
 
     transform.translation += translation.extend(0.0);
 }
 
+fn player_animation_parameters(mut query: Query<(&mut AnimationParameters, &Player)>) {
+    let Ok((mut parameters, player)) = query.get_single_mut() else {
+        return;
+    };
+
+    parameters.direction = player.direction;
+    parameters.state = player.player_state.animation_state();
+}
+
+/// The wave is lost once the keep's death animation has played through.
 fn player_death(
+    mut finished_events: EventReader<AnimationFinished>,
     mut next_state: ResMut<NextState<WaveState>>,
-    mut query: Query<(&mut Player, &mut SimpleAnimation)>,
-    time: Res<Time>,
+    query: Query<(Entity, &Player, &SimpleAnimation)>,
 ) {
-    let Ok((mut player, mut player_animation)) = query.get_single_mut() else {
+    let Ok((player_entity, player, player_animation)) = query.get_single() else {
         return;
     };
 
@@ -139,35 +209,23 @@ fn player_death(
         return;
     }
 
-    player_animation.current_frame_index = 0;
-    player_animation.frames = vec![3];
-
-    if player.death_timer.finished() {
-        return;
-    }
-
-    player.death_timer.tick(time.delta());
-
-    if player.death_timer.just_finished() {
-        next_state.set(WaveState::GameOver);
+    for event in finished_events.read() {
+        if event.entity == player_entity && event.clip == player_animation.clip.name {
+            next_state.set(WaveState::GameOver);
+        }
     }
 }
 
-fn player_health(mut query: Query<(&Health, &mut Player)>) {
-    let Ok((health, mut player)) = query.get_single_mut() else {
+fn player_health(mut died_events: EventReader<Died>, mut query: Query<(Entity, &mut Player)>) {
+    let Ok((player_entity, mut player)) = query.get_single_mut() else {
         return;
     };
 
-    if player.player_state == PlayerState::Dead {
-        return;
-    }
-
-    if health.current > 0 {
-        return;
+    for event in died_events.read() {
+        if event.entity == player_entity {
+            player.player_state = PlayerState::Dead;
+        }
     }
-
-    player.death_timer.reset();
-    player.player_state = PlayerState::Dead;
 }
 
 fn player_invincibility(mut query: Query<&mut Player>, time: Res<Time>) {
@@ -186,8 +244,25 @@ fn player_invincibility(mut query: Query<&mut Player>, time: Res<Time>) {
     }
 }
 
-fn steer_player(mut query: Query<(&ActionState<Action>, &mut Player)>) {
-    for (action_state, mut player) in query.iter_mut() {
+/// Carries the crew's wounds over to the next wave.
+fn save_crew(mut game_controller: ResMut<GameController>, query: Query<(&Defender, &Health)>) {
+    for (defender, health) in query.iter() {
+        let Some(member) = game_controller.crew.get_mut(defender.crew_index) else {
+            continue;
+        };
+
+        member.down = health.state == HealthState::Dead;
+        member.health = health.current;
+    }
+}
+
+fn steer_player(mut query: Query<(&ActionState<Action>, &mut Player, &Footing, &StatusEffects)>) {
+    for (action_state, mut player, footing, status_effects) in query.iter_mut() {
+        // A stunned keep carries on in a straight line.
+        if status_effects.is_stunned() {
+            continue;
+        }
+
         let mut target_direction = Vec2::ZERO;
 
         if action_state.pressed(&Action::MoveUp) {
@@ -209,7 +284,7 @@ fn steer_player(mut query: Query<(&ActionState<Action>, &mut Player)>) {
         if target_direction != Vec2::ZERO {
             target_direction = target_direction.normalize();
 
-            player.direction = player.direction.rotate_towards(target_direction, TURN_RATE);
+            player.direction = player.direction.rotate_towards(target_direction, TURN_RATE * footing.turn_multiplier());
         }
     }
 }
@@ -217,14 +292,16 @@ fn steer_player(mut query: Query<(&ActionState<Action>, &mut Player)>) {
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(DefenderPlugin);
-        app.add_systems(OnExit(WaveState::Running), destroy_player);
+        app.add_systems(OnExit(WaveState::Running), (save_crew, destroy_player).chain());
         app.add_systems(
             Update,
             (
                 follow_player,
                 initialize_player,
+                low_health_warning,
                 move_player,
-                player_death,
+                player_animation_parameters.after(player_health),
+                player_death.before(player_health),
                 player_health,
                 player_invincibility,
                 steer_player,
@@ -0,0 +1,322 @@
+use bevy::{math::bounding::*, prelude::*};
+
+use crate::asset_handles::AssetHandles;
+use crate::game::game_sets::PausableSet;
+use crate::health::{DamageEvent, DamageType, Health, HealthState};
+
+use super::enemy::Enemy;
+use super::knockback::KnockbackEvent;
+use super::prop::PropTile;
+use super::status_effect::{StatusEffect, StatusEffectEvent};
+use super::tile_collision::TileCollision;
+use super::wave_sets::WaveRunningSet;
+use super::wave_state::WaveState;
+
+const DEFAULT_KNOCKBACK: f32 = 80.0;
+const DEFAULT_LIFETIME: f32 = 20.0;
+const DEFAULT_SPEED: f32 = 200.0;
+const PROJECTILE_SIZE: f32 = 4.0;
+const RICOCHET_RANGE: f32 = 96.0;
+
+#[derive(Clone, Copy, Default, Eq, PartialEq)]
+pub enum Flight {
+    /// Turns to follow its target for as long as the target is alive, then carries straight on.
+    #[default]
+    Homing,
+    /// Flies in a straight line towards where its target was when fired.
+    Straight,
+}
+
+#[derive(Component)]
+#[require(Sprite, Transform, Visibility)]
+pub struct Projectile {
+    pub damage: u32,
+    pub damage_type: DamageType,
+    pub direction: Vec2,
+    pub flight: Flight,
+    /// The frame of the weapon sprite sheet to show.
+    pub frame: usize,
+    /// Enemies already hit, which are never hit twice.
+    pub hits: Vec<Entity>,
+    /// How hard enemies it hits are pushed away.
+    pub knockback: f32,
+    pub lifetime: Timer,
+    /// Whoever fired this, credited with its damage.
+    pub owner: Option<Entity>,
+    /// How many more enemies it can pass through.
+    pub pierce: u32,
+    /// How many more times it can bounce to a nearby enemy after a hit.
+    pub ricochet: u32,
+    pub speed: f32,
+    /// Damages every other enemy within this distance of a hit too.
+    pub splash_radius: f32,
+    pub status_effect: Option<StatusEffect>,
+    pub target: Option<Entity>,
+}
+
+impl Default for Projectile {
+    fn default() -> Self {
+        Self {
+            damage: 0,
+            damage_type: DamageType::Pierce,
+            direction: Vec2::ZERO,
+            flight: Flight::default(),
+            frame: 0,
+            hits: Vec::new(),
+            knockback: DEFAULT_KNOCKBACK,
+            lifetime: Timer::from_seconds(DEFAULT_LIFETIME, TimerMode::Once),
+            owner: None,
+            pierce: 0,
+            ricochet: 0,
+            speed: DEFAULT_SPEED,
+            splash_radius: 0.0,
+            status_effect: None,
+            target: None,
+        }
+    }
+}
+
+impl Projectile {
+    /// Damages `target`, pushing it along `direction`.
+    fn hit(
+        &self,
+        damage_events: &mut EventWriter<DamageEvent>,
+        knockback_events: &mut EventWriter<KnockbackEvent>,
+        status_effect_events: &mut EventWriter<StatusEffectEvent>,
+        target: Entity,
+        direction: Vec2,
+    ) {
+        damage_events.send(DamageEvent {
+            amount: self.damage,
+            damage_type: self.damage_type,
+            source: self.owner,
+            target,
+        });
+
+        knockback_events.send(KnockbackEvent {
+            force: direction * self.knockback,
+            target,
+        });
+
+        if let Some(effect) = self.status_effect {
+            status_effect_events.send(StatusEffectEvent {
+                effect,
+                source: self.owner,
+                target,
+            });
+        }
+    }
+}
+
+fn destroy_projectiles(mut commands: Commands, query: Query<Entity, With<Projectile>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn initialize_projectile(
+    asset_handles: Res<AssetHandles>,
+    mut query: Query<(&Projectile, &mut Sprite), Added<Projectile>>,
+) {
+    for (projectile, mut sprite) in query.iter_mut() {
+        sprite.image = asset_handles.image("weapon");
+        sprite.texture_atlas = Some(asset_handles.texture_atlas("weapon", projectile.frame));
+
+        if let Some(status_effect) = projectile.status_effect {
+            sprite.color = status_effect.kind.tint();
+        }
+    }
+}
+
+fn projectile_hit(
+    mut commands: Commands,
+    mut damage_events: EventWriter<DamageEvent>,
+    enemy_query: Query<(Entity, &Enemy, &Health, &Transform)>,
+    mut knockback_events: EventWriter<KnockbackEvent>,
+    mut projectile_query: Query<(Entity, &mut Projectile, &Transform)>,
+    mut status_effect_events: EventWriter<StatusEffectEvent>,
+) {
+    for (projectile_entity, mut projectile, projectile_transform) in projectile_query.iter_mut() {
+        let position = projectile_transform.translation.xy();
+        let projectile_volume = BoundingCircle::new(position, PROJECTILE_SIZE);
+
+        // Only the first enemy touched counts each frame, so a projectile that is about to be
+        // despawned never hits twice.
+        let Some(enemy_entity) = enemy_query
+            .iter()
+            .filter(|(entity, _, health, _)| {
+                health.state != HealthState::Dead && !projectile.hits.contains(entity)
+            })
+            .find(|(_, enemy, _, transform)| enemy.volume(transform).intersects(&projectile_volume))
+            .map(|(entity, ..)| entity)
+        else {
+            continue;
+        };
+
+        projectile.hits.push(enemy_entity);
+
+        let direction = projectile.direction;
+
+        projectile.hit(
+            &mut damage_events,
+            &mut knockback_events,
+            &mut status_effect_events,
+            enemy_entity,
+            direction,
+        );
+
+        if projectile.splash_radius > 0.0 {
+            let splash_volume = BoundingCircle::new(position, projectile.splash_radius);
+
+            for (entity, enemy, health, transform) in enemy_query.iter() {
+                if entity == enemy_entity
+                    || health.state == HealthState::Dead
+                    || !enemy.volume(transform).intersects(&splash_volume)
+                {
+                    continue;
+                }
+
+                // Splash pushes everything away from the impact.
+                let direction = (transform.translation.xy() - position).normalize_or_zero();
+
+                projectile.hit(
+                    &mut damage_events,
+                    &mut knockback_events,
+                    &mut status_effect_events,
+                    entity,
+                    direction,
+                );
+            }
+        }
+
+        if projectile.ricochet > 0 {
+            let next_target = enemy_query
+                .iter()
+                .filter(|(entity, _, health, _)| {
+                    health.state != HealthState::Dead && !projectile.hits.contains(entity)
+                })
+                .map(|(entity, _, _, transform)| {
+                    (
+                        entity,
+                        transform.translation.xy().distance(position),
+                        transform,
+                    )
+                })
+                .filter(|(_, distance, _)| *distance <= RICOCHET_RANGE)
+                .min_by(|(_, a, _), (_, b, _)| a.total_cmp(b));
+
+            if let Some((entity, _, transform)) = next_target {
+                projectile.ricochet -= 1;
+                projectile.target = Some(entity);
+                projectile.direction = (transform.translation.xy() - position).normalize_or_zero();
+                continue;
+            }
+        }
+
+        if projectile.pierce > 0 {
+            projectile.pierce -= 1;
+            continue;
+        }
+
+        commands.entity(projectile_entity).despawn();
+    }
+}
+
+fn projectile_lifetime(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Projectile)>,
+    time: Res<Time>,
+) {
+    for (entity, mut projectile) in query.iter_mut() {
+        projectile.lifetime.tick(time.delta());
+
+        if projectile.lifetime.just_finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn projectile_movement(
+    mut commands: Commands,
+    mut damage_events: EventWriter<DamageEvent>,
+    enemy_query: Query<(&Health, &Transform), With<Enemy>>,
+    mut projectile_query: Query<(Entity, &mut Projectile, &mut Transform), Without<Enemy>>,
+    prop_query: Query<(), With<PropTile>>,
+    tiles: TileCollision,
+    time: Res<Time>,
+) {
+    for (projectile_entity, mut projectile, mut projectile_transform) in projectile_query.iter_mut()
+    {
+        // Once the target is dying there's nothing left to follow.
+        if let Some(target) = projectile.target
+            && enemy_query
+                .get(target)
+                .is_ok_and(|(health, _)| health.state == HealthState::Dead)
+        {
+            projectile.target = None;
+        }
+
+        let aim = projectile
+            .target
+            .and_then(|target| enemy_query.get(target).ok())
+            .map(|(_, target_transform)| {
+                (target_transform.translation - projectile_transform.translation)
+                    .truncate()
+                    .normalize_or_zero()
+            });
+
+        // Straight shots only aim once, when they are fired.
+        if let Some(aim) = aim
+            && (projectile.flight == Flight::Homing || projectile.direction == Vec2::ZERO)
+        {
+            projectile.direction = aim;
+        }
+
+        if projectile.direction == Vec2::ZERO {
+            // The target vanished before it could be aimed at.
+            commands.entity(projectile_entity).despawn();
+            continue;
+        }
+
+        let translation = projectile.direction * projectile.speed * time.delta_secs();
+
+        // Obstacles stop everything, even shots that pierce, but props take the hit.
+        if let Some(tile_entity) =
+            tiles.solid_tile(projectile_transform.translation.xy() + translation)
+        {
+            if prop_query.contains(tile_entity) {
+                damage_events.send(DamageEvent {
+                    amount: projectile.damage,
+                    damage_type: projectile.damage_type,
+                    source: projectile.owner,
+                    target: tile_entity,
+                });
+            }
+
+            commands.entity(projectile_entity).despawn();
+            continue;
+        }
+
+        projectile_transform.translation += translation.extend(0.0);
+        projectile_transform.rotation =
+            Quat::from_rotation_arc(Vec3::Y, projectile.direction.extend(0.0));
+    }
+}
+
+pub struct ProjectilePlugin;
+
+impl Plugin for ProjectilePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnExit(WaveState::Running), destroy_projectiles);
+        app.add_systems(
+            Update,
+            (
+                initialize_projectile,
+                projectile_lifetime,
+                (projectile_movement, projectile_hit).chain(),
+            )
+                .in_set(PausableSet)
+                .in_set(WaveRunningSet),
+        );
+    }
+}
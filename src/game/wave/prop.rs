@@ -0,0 +1,116 @@
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::*;
+use bevy_prng::WyRand;
+use bevy_rand::prelude::*;
+use rand::Rng;
+
+use crate::arena_map::Prop;
+use crate::game::game_sets::PausableSet;
+use crate::health::{Died, Health};
+
+use super::WaveTilemap;
+use super::arena::{Arena, TILE_SIZE};
+use super::loot::Loot;
+use super::open_world::OpenWorld;
+use super::tile_collision::SolidTile;
+use super::wave_sets::WaveRunningSet;
+
+const CRATE_HEALTH: u32 = 3;
+const CRATE_LOOT_CHANCE: f64 = 0.6;
+const CRATE_LOOT_VALUE: u32 = 2;
+const FENCE_HEALTH: u32 = 2;
+/// Damage to a prop for every unit per second the keep was driving into it, so a head-on hit at
+/// full speed smashes a crate or fence but takes two for a tree.
+pub const PROP_DAMAGE_PER_SPEED: f32 = 0.025;
+pub const RUBBLE: u32 = 29;
+const TREE_HEALTH: u32 = 6;
+const TREE_LOOT_CHANCE: f64 = 0.25;
+const TREE_LOOT_VALUE: u32 = 1;
+
+/// Marks a tile with a prop standing on it, which is solid until its health runs out.
+#[derive(Component)]
+#[require(Health)]
+pub struct PropTile(pub Prop);
+
+impl PropTile {
+    fn health(&self) -> u32 {
+        match self.0 {
+            Prop::Crate => CRATE_HEALTH,
+            Prop::Fence => FENCE_HEALTH,
+            Prop::Tree => TREE_HEALTH,
+        }
+    }
+
+    /// The chance of dropping gold when smashed, and how much.
+    fn loot(&self) -> Option<(f64, u32)> {
+        match self.0 {
+            Prop::Crate => Some((CRATE_LOOT_CHANCE, CRATE_LOOT_VALUE)),
+            Prop::Fence => None,
+            Prop::Tree => Some((TREE_LOOT_CHANCE, TREE_LOOT_VALUE)),
+        }
+    }
+}
+
+fn initialize_prop(mut query: Query<(&PropTile, &mut Health), Added<PropTile>>) {
+    for (prop_tile, mut health) in query.iter_mut() {
+        health.max = prop_tile.health();
+        health.current = health.max;
+    }
+}
+
+/// Smashed props leave rubble that can be driven over, and sometimes gold. Out in the open world
+/// they stay smashed when their chunk reloads.
+fn smash_props(
+    mut arena: ResMut<Arena>,
+    mut commands: Commands,
+    mut died_events: EventReader<Died>,
+    mut global_rng: GlobalEntropy<WyRand>,
+    mut open_world: Option<ResMut<OpenWorld>>,
+    mut prop_query: Query<(&PropTile, &TilePos, &TilemapId, &mut TileTextureIndex)>,
+    tilemap_query: Query<&GlobalTransform, With<WaveTilemap>>,
+) {
+    for event in died_events.read() {
+        let Ok((prop_tile, tile_pos, tilemap_id, mut texture_index)) =
+            prop_query.get_mut(event.entity)
+        else {
+            continue;
+        };
+
+        let Ok(tilemap_transform) = tilemap_query.get(tilemap_id.0) else {
+            continue;
+        };
+
+        let position =
+            tilemap_transform.translation().xy() + UVec2::from(tile_pos).as_vec2() * TILE_SIZE;
+
+        if let Some((chance, value)) = prop_tile.loot()
+            && global_rng.gen_bool(chance)
+        {
+            Loot::spawn(&mut commands, value, position);
+        }
+
+        *texture_index = TileTextureIndex(RUBBLE);
+        arena.clear(position);
+
+        if let Some(open_world) = open_world.as_mut() {
+            open_world.smash(position);
+        }
+
+        commands
+            .entity(event.entity)
+            .remove::<(Health, PropTile, SolidTile)>();
+    }
+}
+
+pub struct PropPlugin;
+
+impl Plugin for PropPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (initialize_prop, smash_props)
+                .in_set(PausableSet)
+                .in_set(WaveRunningSet),
+        );
+    }
+}
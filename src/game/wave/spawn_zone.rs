@@ -0,0 +1,152 @@
+use bevy::{math::bounding::*, prelude::*};
+use rand::{Rng, seq::SliceRandom};
+
+use crate::colors::DARK_RED;
+use crate::game::game_sets::PausableSet;
+
+use super::enemy::{Enemy, EnemyState};
+use super::wave_sets::WaveRunningSet;
+
+/// Hand-placed spawn points, relative to the middle of the arena.
+pub const AUTHORED_SPAWN_POINTS: [Vec2; 4] = [
+    Vec2::new(-240.0, 0.0),
+    Vec2::new(0.0, -120.0),
+    Vec2::new(0.0, 120.0),
+    Vec2::new(240.0, 0.0),
+];
+const CORNER_SIZE: f32 = 48.0;
+const EDGE_INSET: f32 = 16.0;
+/// Enemies never spawn closer than this to the keep.
+const MIN_KEEP_DISTANCE: f32 = 96.0;
+const POINT_JITTER: f32 = 16.0;
+pub const RING_RADIUS: f32 = 160.0;
+const RING_WIDTH: f32 = 48.0;
+const SPAWN_ATTEMPTS: u32 = 8;
+const TELEGRAPH_PULSE_RATE: f32 = 8.0;
+const TELEGRAPH_SIZE: f32 = 20.0;
+
+/// Where in the arena enemies can appear.
+pub enum SpawnZone {
+    /// Near one of the four corners.
+    Corners,
+    /// Anywhere along the edges.
+    Edges,
+    /// Around one of a set of authored points.
+    Points(Vec<Vec2>),
+    /// A band around the keep, `radius` away from it.
+    Ring { radius: f32 },
+}
+
+impl SpawnZone {
+    fn sample(&self, rng: &mut impl Rng, bounds: &Aabb2d, keep: Vec2) -> Vec2 {
+        let inner_min = bounds.min + EDGE_INSET;
+        let inner_max = bounds.max - EDGE_INSET;
+
+        match self {
+            SpawnZone::Corners => {
+                let corner = Vec2::new(
+                    if rng.gen_bool(0.5) { inner_min.x } else { inner_max.x },
+                    if rng.gen_bool(0.5) { inner_min.y } else { inner_max.y },
+                );
+                let inward = (bounds.center() - corner).signum();
+
+                corner
+                    + inward
+                        * Vec2::new(
+                            rng.gen_range(0.0..CORNER_SIZE),
+                            rng.gen_range(0.0..CORNER_SIZE),
+                        )
+            }
+            SpawnZone::Edges => {
+                let x = rng.gen_range(inner_min.x..inner_max.x);
+                let y = rng.gen_range(inner_min.y..inner_max.y);
+
+                match rng.gen_range(0..4) {
+                    0 => Vec2::new(inner_min.x, y),
+                    1 => Vec2::new(inner_max.x, y),
+                    2 => Vec2::new(x, inner_min.y),
+                    _ => Vec2::new(x, inner_max.y),
+                }
+            }
+            SpawnZone::Points(points) => {
+                let point = points.choose(rng).copied().unwrap_or(bounds.center());
+                let jitter = Vec2::new(
+                    rng.gen_range(-POINT_JITTER..POINT_JITTER),
+                    rng.gen_range(-POINT_JITTER..POINT_JITTER),
+                );
+
+                point + jitter
+            }
+            SpawnZone::Ring { radius } => {
+                let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+                let distance = rng.gen_range(*radius..radius + RING_WIDTH);
+
+                keep + Vec2::from_angle(angle) * distance
+            }
+        }
+        .clamp(inner_min, inner_max)
+    }
+
+    /// A position in this zone that is `open` and at a safe distance from the keep, if one can be
+    /// found.
+    pub fn pick(
+        &self,
+        rng: &mut impl Rng,
+        bounds: &Aabb2d,
+        keep: Vec2,
+        open: impl Fn(Vec2) -> bool,
+    ) -> Option<Vec2> {
+        (0..SPAWN_ATTEMPTS)
+            .map(|_| self.sample(rng, bounds, keep))
+            .find(|position| position.distance(keep) >= MIN_KEEP_DISTANCE && open(*position))
+    }
+}
+
+/// A warning marker under an enemy that is about to appear.
+#[derive(Component)]
+struct SpawnTelegraph;
+
+fn add_spawn_telegraphs(mut commands: Commands, query: Query<Entity, Added<Enemy>>) {
+    for enemy_entity in query.iter() {
+        commands.entity(enemy_entity).with_child((
+            SpawnTelegraph,
+            Sprite::from_color(DARK_RED, Vec2::splat(TELEGRAPH_SIZE)),
+            Transform::from_xyz(0.0, 0.0, -0.5),
+        ));
+    }
+}
+
+fn update_spawn_telegraphs(
+    mut commands: Commands,
+    enemy_query: Query<&Enemy>,
+    mut telegraph_query: Query<(Entity, &Parent, &mut Sprite), With<SpawnTelegraph>>,
+    time: Res<Time>,
+) {
+    let pulse = (time.elapsed_secs() * TELEGRAPH_PULSE_RATE).sin() * 0.25 + 0.5;
+
+    for (telegraph_entity, parent, mut sprite) in telegraph_query.iter_mut() {
+        let spawning = enemy_query
+            .get(parent.get())
+            .is_ok_and(|enemy| enemy.enemy_state == EnemyState::Spawning);
+
+        if !spawning {
+            commands.entity(telegraph_entity).despawn_recursive();
+            continue;
+        }
+
+        sprite.color.set_alpha(pulse);
+    }
+}
+
+pub struct SpawnZonePlugin;
+
+impl Plugin for SpawnZonePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (add_spawn_telegraphs, update_spawn_telegraphs)
+                .in_set(PausableSet)
+                .in_set(WaveRunningSet),
+        );
+    }
+}
@@ -0,0 +1,230 @@
+use bevy::prelude::*;
+
+use crate::colors::{GOLD, ICE_BLUE, LIME_GREEN, ORANGE};
+use crate::game::game_sets::PausableSet;
+use crate::health::{DamageEvent, DamageType};
+
+use super::wave_sets::WaveRunningSet;
+
+const DAMAGE_TICK_RATE: f32 = 1.0;
+const MAX_POISON_STACKS: u32 = 5;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum StatusEffectKind {
+    /// Fire damage over time.
+    Burn,
+    /// Poison damage over time, stacking with each hit.
+    Poison,
+    /// Reduces movement speed by `magnitude`, from 0 to 1.
+    Slow,
+    /// Stops the target from acting at all.
+    Stun,
+}
+
+impl StatusEffectKind {
+    pub fn tint(&self) -> Color {
+        match self {
+            StatusEffectKind::Burn => ORANGE,
+            StatusEffectKind::Poison => LIME_GREEN,
+            StatusEffectKind::Slow => ICE_BLUE,
+            StatusEffectKind::Stun => GOLD,
+        }
+    }
+
+    /// Which effect's tint shows when several are active. Higher wins.
+    fn tint_priority(&self) -> u32 {
+        match self {
+            StatusEffectKind::Slow => 0,
+            StatusEffectKind::Poison => 1,
+            StatusEffectKind::Burn => 2,
+            StatusEffectKind::Stun => 3,
+        }
+    }
+}
+
+/// A timed effect that a hit can apply.
+#[derive(Clone, Copy, Debug)]
+pub struct StatusEffect {
+    pub duration: f32,
+    pub kind: StatusEffectKind,
+    /// Damage per second for burn and poison, or the fraction of speed lost for slow.
+    pub magnitude: f32,
+}
+
+impl StatusEffect {
+    pub fn new(kind: StatusEffectKind, duration: f32, magnitude: f32) -> Self {
+        Self {
+            duration,
+            kind,
+            magnitude,
+        }
+    }
+}
+
+struct ActiveStatusEffect {
+    damage_timer: Timer,
+    effect: StatusEffect,
+    remaining: Timer,
+    source: Option<Entity>,
+    stacks: u32,
+}
+
+impl ActiveStatusEffect {
+    /// Extends the effect to `duration` if that is longer than what is left.
+    fn refresh(&mut self, duration: f32) {
+        if self.remaining.remaining_secs() < duration {
+            self.remaining = Timer::from_seconds(duration, TimerMode::Once);
+        }
+    }
+}
+
+/// The effects currently on an entity. Each kind is only ever active once, and reapplying it
+/// follows the stacking rules in `add`.
+#[derive(Component, Default)]
+pub struct StatusEffects(Vec<ActiveStatusEffect>);
+
+impl StatusEffects {
+    /// Applies `effect`. Burn and slow keep the strongest magnitude, poison gains a stack (up to
+    /// `MAX_POISON_STACKS`), and stun never stacks. Every kind keeps the longer duration.
+    pub fn add(&mut self, effect: StatusEffect, source: Option<Entity>) {
+        let Some(active) = self
+            .0
+            .iter_mut()
+            .find(|active| active.effect.kind == effect.kind)
+        else {
+            self.0.push(ActiveStatusEffect {
+                damage_timer: Timer::from_seconds(DAMAGE_TICK_RATE, TimerMode::Repeating),
+                effect,
+                remaining: Timer::from_seconds(effect.duration, TimerMode::Once),
+                source,
+                stacks: 1,
+            });
+            return;
+        };
+
+        active.effect.magnitude = active.effect.magnitude.max(effect.magnitude);
+        active.refresh(effect.duration);
+        active.source = source;
+
+        if effect.kind == StatusEffectKind::Poison {
+            active.stacks = (active.stacks + 1).min(MAX_POISON_STACKS);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    pub fn is_stunned(&self) -> bool {
+        self.0
+            .iter()
+            .any(|active| active.effect.kind == StatusEffectKind::Stun)
+    }
+
+    /// What movement speed should be scaled by.
+    pub fn speed_multiplier(&self) -> f32 {
+        let slow = self
+            .0
+            .iter()
+            .filter(|active| active.effect.kind == StatusEffectKind::Slow)
+            .map(|active| active.effect.magnitude)
+            .fold(0.0, f32::max);
+
+        1.0 - slow.clamp(0.0, 1.0)
+    }
+
+    fn tint(&self) -> Color {
+        self.0
+            .iter()
+            .map(|active| active.effect.kind)
+            .max_by_key(StatusEffectKind::tint_priority)
+            .map_or(Color::WHITE, |kind| kind.tint())
+    }
+}
+
+/// A request to apply `effect` to `target`, usually alongside a `DamageEvent`.
+#[derive(Event)]
+pub struct StatusEffectEvent {
+    pub effect: StatusEffect,
+    pub source: Option<Entity>,
+    pub target: Entity,
+}
+
+fn apply_status_effects(
+    mut query: Query<&mut StatusEffects>,
+    mut status_effect_events: EventReader<StatusEffectEvent>,
+) {
+    for event in status_effect_events.read() {
+        let Ok(mut status_effects) = query.get_mut(event.target) else {
+            continue;
+        };
+
+        status_effects.add(event.effect, event.source);
+    }
+}
+
+fn tick_status_effects(
+    mut damage_events: EventWriter<DamageEvent>,
+    mut query: Query<(Entity, &mut StatusEffects)>,
+    time: Res<Time>,
+) {
+    for (entity, mut status_effects) in query.iter_mut() {
+        if status_effects.0.is_empty() {
+            continue;
+        }
+
+        for active in status_effects.0.iter_mut() {
+            active.remaining.tick(time.delta());
+
+            let damage_type = match active.effect.kind {
+                StatusEffectKind::Burn => DamageType::Fire,
+                StatusEffectKind::Poison => DamageType::Poison,
+                StatusEffectKind::Slow | StatusEffectKind::Stun => continue,
+            };
+
+            active.damage_timer.tick(time.delta());
+
+            for _ in 0..active.damage_timer.times_finished_this_tick() {
+                damage_events.send(DamageEvent {
+                    amount: (active.effect.magnitude * active.stacks as f32).round() as u32,
+                    damage_type,
+                    source: active.source,
+                    target: entity,
+                });
+            }
+        }
+
+        status_effects
+            .0
+            .retain(|active| !active.remaining.finished());
+    }
+}
+
+fn tint_status_effects(mut query: Query<(&mut Sprite, &StatusEffects), Changed<StatusEffects>>) {
+    for (mut sprite, status_effects) in query.iter_mut() {
+        let tint = status_effects.tint();
+
+        if sprite.color != tint {
+            sprite.color = tint;
+        }
+    }
+}
+
+pub struct StatusEffectPlugin;
+
+impl Plugin for StatusEffectPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<StatusEffectEvent>();
+        app.add_systems(
+            Update,
+            (
+                apply_status_effects,
+                tick_status_effects,
+                tint_status_effects,
+            )
+                .chain()
+                .in_set(PausableSet)
+                .in_set(WaveRunningSet),
+        );
+    }
+}
@@ -0,0 +1,162 @@
+use bevy::{math::bounding::*, prelude::*, utils::HashMap};
+
+use super::enemy::{Enemy, EnemyState};
+
+const AVOIDANCE_DISTANCE: f32 = 32.0;
+const CELL_SIZE: f32 = 32.0;
+/// Enemies closer than this to the keep stop flanking and go straight for it.
+const ENGAGE_DISTANCE: f32 = 48.0;
+/// How far out flanking enemies swing their widest.
+const FLANK_DISTANCE: f32 = 160.0;
+pub const MAX_FLANK_ANGLE: f32 = std::f32::consts::FRAC_PI_2;
+/// How far ahead enemies look for obstacles to steer around.
+const OBSTACLE_LOOKAHEAD: f32 = 24.0;
+
+/// How strongly each steering behaviour pulls on an enemy, on top of heading for the keep.
+#[derive(Clone, Copy)]
+pub struct SteeringWeights {
+    /// Turning away from the edges of the arena.
+    pub avoidance: f32,
+    /// Drifting towards the middle of nearby enemies.
+    pub cohesion: f32,
+    /// Swinging around the keep to attack from the enemy's own angle.
+    pub flank: f32,
+    /// How far away other enemies count as neighbours.
+    pub neighbour_radius: f32,
+    /// Keeping clear of nearby enemies.
+    pub separation: f32,
+    /// How far from the keep the enemy stops closing in and circles it instead. Zero goes all
+    /// the way.
+    pub standoff: f32,
+}
+
+/// Active enemies bucketed by position, for cheap neighbour queries.
+#[derive(Default, Resource)]
+pub struct SpatialGrid {
+    cells: HashMap<IVec2, Vec<(Entity, Vec2)>>,
+}
+
+impl SpatialGrid {
+    fn cell(position: Vec2) -> IVec2 {
+        (position / CELL_SIZE).floor().as_ivec2()
+    }
+
+    pub fn insert(&mut self, entity: Entity, position: Vec2) {
+        self.cells
+            .entry(Self::cell(position))
+            .or_default()
+            .push((entity, position));
+    }
+
+    /// Every entity within `radius` of `position`, including one at `position` itself.
+    pub fn neighbours(
+        &self,
+        position: Vec2,
+        radius: f32,
+    ) -> impl Iterator<Item = (Entity, Vec2)> + '_ {
+        let min = Self::cell(position - radius);
+        let max = Self::cell(position + radius);
+
+        (min.x..=max.x)
+            .flat_map(move |x| (min.y..=max.y).map(move |y| IVec2::new(x, y)))
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .copied()
+            .filter(move |(_, other)| other.distance_squared(position) <= radius * radius)
+    }
+}
+
+pub fn update_spatial_grid(
+    mut grid: ResMut<SpatialGrid>,
+    query: Query<(Entity, &Enemy, &Transform)>,
+) {
+    grid.cells.clear();
+
+    for (entity, enemy, transform) in query.iter() {
+        if enemy.enemy_state != EnemyState::Active {
+            continue;
+        }
+
+        grid.insert(entity, transform.translation.xy());
+    }
+}
+
+/// The direction `entity` at `position` should move in to close in on `target` alongside its
+/// neighbours, staying inside `bounds` and going around anywhere `blocked`.
+pub fn steering_direction(
+    entity: Entity,
+    position: Vec2,
+    target: Vec2,
+    flank_angle: f32,
+    weights: SteeringWeights,
+    grid: &SpatialGrid,
+    bounds: &Aabb2d,
+    blocked: impl Fn(Vec2) -> bool,
+) -> Vec2 {
+    let offset = target - position;
+    let seek = offset.normalize_or_zero();
+
+    // Flanking fades out as the enemy closes in, so it always reaches the keep in the end.
+    let flank_falloff = ((offset.length() - ENGAGE_DISTANCE) / FLANK_DISTANCE).clamp(0.0, 1.0);
+    let mut approach = Vec2::from_angle(flank_angle * weights.flank * flank_falloff).rotate(seek);
+
+    // Inside its standoff distance, an enemy backs off and circles the keep the way it flanks.
+    if offset.length() < weights.standoff {
+        approach = seek.perp() * flank_angle.signum() - seek * 0.5;
+    }
+
+    // Slip around obstacles in the way rather than pushing into them.
+    if blocked(position + approach.normalize_or_zero() * OBSTACLE_LOOKAHEAD) {
+        let side = approach.perp() * flank_angle.signum();
+
+        approach = if blocked(position + side.normalize_or_zero() * OBSTACLE_LOOKAHEAD) {
+            -side
+        } else {
+            side
+        };
+    }
+
+    let mut separation = Vec2::ZERO;
+    let mut centre = Vec2::ZERO;
+    let mut neighbour_count = 0;
+
+    for (other_entity, other_position) in grid.neighbours(position, weights.neighbour_radius) {
+        if other_entity == entity {
+            continue;
+        }
+
+        let away = position - other_position;
+        let closeness = 1.0 - away.length() / weights.neighbour_radius;
+
+        separation += away.normalize_or_zero() * closeness;
+        centre += other_position;
+        neighbour_count += 1;
+    }
+
+    let cohesion = if neighbour_count > 0 {
+        (centre / neighbour_count as f32 - position).normalize_or_zero()
+    } else {
+        Vec2::ZERO
+    };
+
+    let mut avoidance = Vec2::ZERO;
+
+    for axis in [Vec2::X, Vec2::Y] {
+        let near_min = (position - bounds.min).dot(axis);
+        let near_max = (bounds.max - position).dot(axis);
+
+        if near_min < AVOIDANCE_DISTANCE {
+            avoidance += axis * (1.0 - near_min / AVOIDANCE_DISTANCE);
+        }
+
+        if near_max < AVOIDANCE_DISTANCE {
+            avoidance -= axis * (1.0 - near_max / AVOIDANCE_DISTANCE);
+        }
+    }
+
+    (approach
+        + separation * weights.separation
+        + cohesion * weights.cohesion
+        + avoidance * weights.avoidance)
+        .normalize_or(seek)
+}
@@ -0,0 +1,141 @@
+use bevy::{ecs::system::SystemParam, math::bounding::*, prelude::*};
+use bevy_ecs_tilemap::prelude::*;
+
+use crate::arena_map::Hazard;
+
+use super::WaveTilemap;
+use super::hazard::HazardTile;
+
+/// Marks a tile that the keep, enemies and projectiles can't pass through.
+#[derive(Component)]
+pub struct SolidTile;
+
+/// Looks up the solid and hazard tiles of the wave's tilemaps by world position. Every tilemap
+/// is laid out on the same grid, so a tile's position doesn't depend on which one it is in.
+#[derive(SystemParam)]
+pub struct TileCollision<'w, 's> {
+    hazard_query: Query<'w, 's, &'static HazardTile>,
+    solid_query: Query<'w, 's, (), With<SolidTile>>,
+    tilemap_query: Query<
+        'w,
+        's,
+        (
+            &'static TileStorage,
+            &'static TilemapSize,
+            &'static TilemapGridSize,
+            &'static GlobalTransform,
+        ),
+        With<WaveTilemap>,
+    >,
+}
+
+impl TileCollision<'_, '_> {
+    /// The tile `position` is inside, if it is on any tilemap.
+    fn tile_entity(&self, position: Vec2) -> Option<Entity> {
+        self.tilemap_query
+            .iter()
+            .find_map(|(storage, size, grid_size, transform)| {
+                let local = (position - transform.translation().xy()) / Vec2::from(grid_size);
+                let tile = (local + 0.5).floor().as_ivec2();
+
+                TilePos::from_i32_pair(tile.x, tile.y, size)
+                    .and_then(|tile_pos| storage.get(&tile_pos))
+            })
+    }
+
+    /// The hazard `position` is on, if any.
+    pub fn hazard(&self, position: Vec2) -> Option<Hazard> {
+        self.tile_entity(position)
+            .and_then(|tile_entity| self.hazard_query.get(tile_entity).ok())
+            .map(|hazard_tile| hazard_tile.0)
+    }
+
+    /// Whether `position` is inside a solid tile.
+    pub fn is_solid(&self, position: Vec2) -> bool {
+        self.solid_tile(position).is_some()
+    }
+
+    /// The solid tile `position` is inside, if any.
+    pub fn solid_tile(&self, position: Vec2) -> Option<Entity> {
+        self.tile_entity(position)
+            .filter(|tile_entity| self.solid_query.contains(*tile_entity))
+    }
+
+    /// Every solid tile `volume` overlaps, along with its bounds.
+    pub fn overlapping(&self, volume: &BoundingCircle) -> Vec<(Entity, Aabb2d)> {
+        self.solid_tiles_near(volume)
+            .into_iter()
+            .filter(|(_, tile)| {
+                tile.closest_point(volume.center).distance(volume.center) < volume.radius()
+            })
+            .collect()
+    }
+
+    /// How far `volume` has to move to stop overlapping any solid tile. Zero if it already
+    /// doesn't.
+    pub fn push_out(&self, volume: &BoundingCircle) -> Vec2 {
+        self.push_out_ignoring(volume, &[])
+    }
+
+    /// Like `push_out`, but passing straight through the `ignored` tiles.
+    pub fn push_out_ignoring(&self, volume: &BoundingCircle, ignored: &[Entity]) -> Vec2 {
+        let mut push = Vec2::ZERO;
+
+        for (tile_entity, tile) in self.solid_tiles_near(volume) {
+            if ignored.contains(&tile_entity) {
+                continue;
+            }
+
+            let centre = volume.center + push;
+            let closest = tile.closest_point(centre);
+            let away = centre - closest;
+            let distance = away.length();
+
+            if distance >= volume.radius() {
+                continue;
+            }
+
+            push += if distance > 0.0 {
+                away / distance * (volume.radius() - distance)
+            } else {
+                // Buried in the tile, so back out along whichever side is nearest.
+                let to_centre = centre - tile.center();
+                let depth = tile.half_size() - to_centre.abs() + volume.radius();
+
+                if depth.x < depth.y {
+                    Vec2::X * depth.x * to_centre.x.signum()
+                } else {
+                    Vec2::Y * depth.y * to_centre.y.signum()
+                }
+            };
+        }
+
+        push
+    }
+
+    /// The solid tiles under the square around `volume`, along with their bounds.
+    fn solid_tiles_near(&self, volume: &BoundingCircle) -> Vec<(Entity, Aabb2d)> {
+        let Some((_, _, grid_size, transform)) = self.tilemap_query.iter().next() else {
+            return Vec::new();
+        };
+
+        let grid_size = Vec2::from(grid_size);
+        let origin = transform.translation().xy();
+        let min = ((volume.center - volume.radius() - origin) / grid_size + 0.5)
+            .floor()
+            .as_ivec2();
+        let max = ((volume.center + volume.radius() - origin) / grid_size + 0.5)
+            .floor()
+            .as_ivec2();
+
+        (min.x..=max.x)
+            .flat_map(|x| (min.y..=max.y).map(move |y| IVec2::new(x, y)))
+            .filter_map(|tile| {
+                let tile_centre = origin + tile.as_vec2() * grid_size;
+
+                self.solid_tile(tile_centre)
+                    .map(|tile_entity| (tile_entity, Aabb2d::new(tile_centre, grid_size / 2.0)))
+            })
+            .collect()
+    }
+}
@@ -1,47 +1,149 @@
 use std::time::Duration;
 
-use bevy::prelude::*;
+use bevy::{prelude::*, time::Stopwatch};
 
 use crate::{app_state::AppState, game::game_state::GameState};
 
-use super::wave_state::WaveState;
+use super::{
+    announcement::Announcement,
+    spawn_zone::{AUTHORED_SPAWN_POINTS, RING_RADIUS, SpawnZone},
+    wave_state::WaveState,
+};
 
+const ARENA_SHIFT_CHANCE_PER_LEVEL: f32 = 0.1;
+const BRUTE_CHANCE_PER_LEVEL: f32 = 0.1;
+const ELITE_CHANCE_PER_LEVEL: f32 = 0.05;
 const ENEMY_SPAWN_AMOUNT: u32 = 1;
+/// How many seconds of an endless run count for as much as a whole wave of difficulty.
+const ENDLESS_SECONDS_PER_LEVEL: f32 = 20.0;
+const HUNTER_CHANCE_PER_LEVEL: f32 = 0.05;
+const MAX_ARENA_SHIFT_CHANCE: f32 = 0.5;
+const MAX_BRUTE_CHANCE: f32 = 0.5;
+const MAX_ELITE_CHANCE: f32 = 0.4;
+const MAX_HUNTER_CHANCE: f32 = 0.3;
+/// Waves between elites being able to roll another affix.
+const LEVELS_PER_ELITE_AFFIX: u32 = 4;
 const ENEMY_SPAWN_INTERVAL: f32 = 5.0;
-const TRANSITION_RATE: f32 = 3.0;
+const ENEMY_SPAWN_INTERVAL_PER_LEVEL: f32 = 0.5;
+const MIN_ENEMY_SPAWN_INTERVAL: f32 = 0.5;
+pub const TRANSITION_RATE: f32 = 3.0;
 const WAVE_RATE: f32 = 15.0;
 
 #[derive(Resource)]
 
 pub struct WaveController {
+    /// The chance of the arena's walls closing in over the wave.
+    pub arena_shift_chance: f32,
+    /// The chance of each spawned enemy being a brute.
+    pub brute_chance: f32,
+    /// The chance of each spawned enemy being an elite.
+    pub elite_chance: f32,
+    pub elite_max_affixes: usize,
     pub enemy_spawn_amount: u32,
     pub enemy_spawn_timer: Timer,
+    /// The wave never finishes, and gets harder for as long as the keep survives.
+    pub endless: bool,
     pub finish_timer: Timer,
     pub game_over_timer: Timer,
+    /// The chance of each spawned enemy going after the crew instead of the keep.
+    pub hunter_chance: f32,
     pub preparation_state: u32,
     pub preparation_timer: Timer,
+    /// Each enemy spawns in one of these, picked at random.
+    pub spawn_zones: Vec<SpawnZone>,
+    /// How long the keep has lasted in an endless wave.
+    pub survived: Stopwatch,
     pub wave_timer: Timer,
 }
 
 impl WaveController {
     pub fn from_level(level: u32) -> Self {
-        let enemy_spawn_amount = ENEMY_SPAWN_AMOUNT + (level / 2);
-        let enemy_spawn_interval = ENEMY_SPAWN_INTERVAL - (level as f32 * 0.5);
-
-        Self {
-            enemy_spawn_amount,
-            enemy_spawn_timer: Timer::from_seconds(enemy_spawn_interval, TimerMode::Repeating),
+        let mut wave_controller = Self {
+            // The first two waves always keep still.
+            arena_shift_chance: (level.saturating_sub(1) as f32 * ARENA_SHIFT_CHANCE_PER_LEVEL)
+                .min(MAX_ARENA_SHIFT_CHANCE),
+            brute_chance: 0.0,
+            elite_chance: 0.0,
+            elite_max_affixes: 1,
+            enemy_spawn_amount: ENEMY_SPAWN_AMOUNT,
+            enemy_spawn_timer: Timer::from_seconds(ENEMY_SPAWN_INTERVAL, TimerMode::Repeating),
+            endless: false,
             finish_timer: Timer::from_seconds(TRANSITION_RATE, TimerMode::Once),
             game_over_timer: Timer::from_seconds(TRANSITION_RATE, TimerMode::Once),
+            hunter_chance: 0.0,
             preparation_state: 3,
             preparation_timer: Timer::from_seconds(1.0, TimerMode::Once),
+            spawn_zones: Self::spawn_zones(level),
+            survived: Stopwatch::new(),
             wave_timer: Timer::from_seconds(WAVE_RATE, TimerMode::Once),
+        };
+
+        wave_controller.set_difficulty(level as f32);
+        wave_controller
+    }
+
+    /// A single wave that starts as easy as the first and ramps up from there. Enemies come from
+    /// every direction from the start, and the walls never move.
+    pub fn endless() -> Self {
+        Self {
+            arena_shift_chance: 0.0,
+            endless: true,
+            spawn_zones: Self::spawn_zones(u32::MAX),
+            ..Self::from_level(0)
         }
     }
-}
 
+    /// Sets how often enemies spawn, how many and which kinds for `level`, which can fall between
+    /// waves.
+    fn set_difficulty(&mut self, level: f32) {
+        let enemy_spawn_interval = (ENEMY_SPAWN_INTERVAL - level * ENEMY_SPAWN_INTERVAL_PER_LEVEL)
+            .max(MIN_ENEMY_SPAWN_INTERVAL);
+
+        self.brute_chance = (level * BRUTE_CHANCE_PER_LEVEL).min(MAX_BRUTE_CHANCE);
+        self.elite_chance = (level * ELITE_CHANCE_PER_LEVEL).min(MAX_ELITE_CHANCE);
+        self.elite_max_affixes = (1 + level as u32 / LEVELS_PER_ELITE_AFFIX) as usize;
+        self.enemy_spawn_amount = ENEMY_SPAWN_AMOUNT + level as u32 / 2;
+        self.enemy_spawn_timer
+            .set_duration(Duration::from_secs_f32(enemy_spawn_interval));
+        self.hunter_chance = (level * HUNTER_CHANCE_PER_LEVEL).min(MAX_HUNTER_CHANCE);
+    }
+
+    /// Later waves come at the keep from more directions.
+    fn spawn_zones(level: u32) -> Vec<SpawnZone> {
+        let mut spawn_zones = vec![SpawnZone::Edges];
+
+        if level >= 1 {
+            spawn_zones.push(SpawnZone::Corners);
+        }
+
+        if level >= 2 {
+            spawn_zones.push(SpawnZone::Ring {
+                radius: RING_RADIUS,
+            });
+        }
+
+        if level >= 3 {
+            spawn_zones.push(SpawnZone::Points(AUTHORED_SPAWN_POINTS.to_vec()));
+        }
+
+        spawn_zones
+    }
+
+    pub fn countdown_announcement(&self) -> Announcement {
+        let message = if self.preparation_state == 0 {
+            "Go!".to_string()
+        } else {
+            self.preparation_state.to_string()
+        };
+
+        Announcement::new(message)
+            .with_duration(self.preparation_timer.duration().as_secs_f32())
+            .with_priority(1)
+    }
+}
 
 pub fn wave_timer_tick(
+    mut announcement_events: EventWriter<Announcement>,
     mut next_app_state: ResMut<NextState<AppState>>,
     mut next_stage_state: ResMut<NextState<GameState>>,
     mut next_wave_state: ResMut<NextState<WaveState>>,
@@ -49,7 +151,6 @@ pub fn wave_timer_tick(
     mut wave_controller: ResMut<WaveController>,
     wave_state: Res<State<WaveState>>,
 ) {
-
     match wave_state.get() {
         WaveState::Complete => {
             wave_controller.finish_timer.tick(time.delta());
@@ -72,6 +173,7 @@ pub fn wave_timer_tick(
                 if wave_controller.preparation_state > 0 {
                     wave_controller.preparation_state -= 1;
                     wave_controller.preparation_timer.reset();
+                    announcement_events.send(wave_controller.countdown_announcement());
                 } else {
                     next_wave_state.set(WaveState::Running);
                     wave_controller.wave_timer.reset();
@@ -81,6 +183,13 @@ pub fn wave_timer_tick(
                 }
             }
         }
+        WaveState::Running if wave_controller.endless => {
+            wave_controller.survived.tick(time.delta());
+
+            let level = wave_controller.survived.elapsed_secs() / ENDLESS_SECONDS_PER_LEVEL;
+
+            wave_controller.set_difficulty(level);
+        }
         WaveState::Running => {
             wave_controller.wave_timer.tick(time.delta());
 
@@ -90,4 +199,4 @@ pub fn wave_timer_tick(
             }
         }
     }
-}
\ No newline at end of file
+}
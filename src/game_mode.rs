@@ -0,0 +1,11 @@
+use bevy::prelude::*;
+
+/// The kind of run started from the main menu.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Resource)]
+pub enum GameMode {
+    /// One wave that never ends and only gets harder, scored by how long the keep survives.
+    Endless,
+    /// Waves of rising difficulty, with a trip to the shop between each.
+    #[default]
+    Waves,
+}
@@ -1,6 +1,17 @@
-use bevy::prelude::*;
+use bevy::{prelude::*, utils::HashMap};
 
-#[derive(Default)]
+use crate::animation_controller::AnimationParameters;
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum DamageType {
+    #[default]
+    Blunt,
+    Fire,
+    Pierce,
+    Poison,
+}
+
+#[derive(Debug, Default, Eq, PartialEq)]
 pub enum HealthState {
     #[default]
     Alive,
@@ -9,11 +20,37 @@ pub enum HealthState {
 
 #[derive(Component, Default)]
 pub struct Health {
+    /// Flat reduction applied to every hit, after resistances.
+    pub armor: u32,
     pub current: u32,
     pub max: u32,
+    /// The fraction of each damage type that is ignored, from 0 to 1.
+    pub resistances: HashMap<DamageType, f32>,
+    /// Soaks up damage before `current` does, and does not come back.
+    pub shield: u32,
     pub state: HealthState,
 }
 
+impl Health {
+    /// How much of a hit actually gets through. Armour never reduces a hit below 1, so it can
+    /// only be fully blocked with a resistance.
+    pub fn mitigate(&self, amount: u32, damage_type: DamageType) -> u32 {
+        let resistance = self
+            .resistances
+            .get(&damage_type)
+            .copied()
+            .unwrap_or(0.0)
+            .clamp(0.0, 1.0);
+        let resisted = (amount as f32 * (1.0 - resistance)).round() as u32;
+
+        if resisted == 0 {
+            return 0;
+        }
+
+        resisted.saturating_sub(self.armor).max(1)
+    }
+}
+
 impl From<u32> for Health {
     fn from(value: u32) -> Self {
         Self {
@@ -22,4 +59,87 @@ impl From<u32> for Health {
             ..default()
         }
     }
-}
\ No newline at end of file
+}
+
+/// A request to hurt `target`. Send this rather than changing `Health` directly.
+#[derive(Event)]
+pub struct DamageEvent {
+    pub amount: u32,
+    pub damage_type: DamageType,
+    /// Whoever dealt the damage, such as the defender that fired a weapon.
+    pub source: Option<Entity>,
+    pub target: Entity,
+}
+
+/// Sent for every hit that took any health off its target, once armour, resistances and shields
+/// have had their say.
+#[derive(Event)]
+pub struct DamageDealt {
+    /// The health actually lost.
+    pub amount: u32,
+    pub source: Option<Entity>,
+    pub target: Entity,
+}
+
+/// Sent once when an entity's health reaches zero.
+#[derive(Event)]
+pub struct Died {
+    pub entity: Entity,
+    /// The source of the damage that finished it off.
+    pub killer: Option<Entity>,
+}
+
+fn apply_damage(
+    mut damage_dealt_events: EventWriter<DamageDealt>,
+    mut damage_events: EventReader<DamageEvent>,
+    mut died_events: EventWriter<Died>,
+    mut query: Query<(&mut Health, Option<&mut AnimationParameters>)>,
+) {
+    for event in damage_events.read() {
+        let Ok((mut health, parameters)) = query.get_mut(event.target) else {
+            continue;
+        };
+
+        if health.state == HealthState::Dead {
+            continue;
+        }
+
+        let mut amount = health.mitigate(event.amount, event.damage_type);
+        let absorbed = amount.min(health.shield);
+
+        health.shield -= absorbed;
+        amount = (amount - absorbed).min(health.current);
+        health.current -= amount;
+
+        if amount > 0 {
+            damage_dealt_events.send(DamageDealt {
+                amount,
+                source: event.source,
+                target: event.target,
+            });
+        }
+
+        if let Some(mut parameters) = parameters {
+            parameters.hit = true;
+        }
+
+        if health.current == 0 {
+            health.state = HealthState::Dead;
+            died_events.send(Died {
+                entity: event.target,
+                killer: event.source,
+            });
+        }
+    }
+}
+
+pub struct HealthPlugin;
+
+impl Plugin for HealthPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<DamageDealt>();
+        app.add_event::<DamageEvent>();
+        app.add_event::<Died>();
+        app.add_systems(Update, apply_damage);
+    }
+}
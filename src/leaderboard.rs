@@ -0,0 +1,117 @@
+#[cfg(not(target_family = "wasm"))]
+use std::{env, path::PathBuf};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// The game's folder in the player's data directory.
+#[cfg(not(target_family = "wasm"))]
+const DATA_DIRECTORY: &str = "keep_the_keep_moving";
+#[cfg(not(target_family = "wasm"))]
+const LEADERBOARD_FILE: &str = "endless_leaderboard.ron";
+const MAX_ENTRIES: usize = 10;
+
+/// Where the leaderboard is kept between runs, in the platform's per-user data directory so it
+/// doesn't matter where the game was started from. Web builds only keep it for the session.
+#[cfg(not(target_family = "wasm"))]
+fn leaderboard_path() -> Option<PathBuf> {
+    let data_directory = if cfg!(target_os = "windows") {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        env::var_os("HOME").map(|home| PathBuf::from(home).join("Library/Application Support"))
+    } else {
+        env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .filter(|path| path.is_absolute())
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+    };
+
+    data_directory.map(|directory| directory.join(DATA_DIRECTORY).join(LEADERBOARD_FILE))
+}
+
+/// The longest endless runs survived on this machine, kept apart from the normal game.
+#[derive(Default, Deserialize, Resource, Serialize)]
+pub struct EndlessLeaderboard {
+    /// Seconds survived, longest first.
+    pub times: Vec<f32>,
+}
+
+impl EndlessLeaderboard {
+    /// Adds a run to the board, returning the place it took, from 0 for the best, if it was
+    /// long enough to make it on.
+    pub fn record(&mut self, time: f32) -> Option<usize> {
+        let place = self
+            .times
+            .iter()
+            .position(|best| time > *best)
+            .unwrap_or(self.times.len());
+
+        if place >= MAX_ENTRIES {
+            return None;
+        }
+
+        self.times.insert(place, time);
+        self.times.truncate(MAX_ENTRIES);
+        self.save();
+
+        Some(place)
+    }
+
+    #[cfg(not(target_family = "wasm"))]
+    fn load() -> Self {
+        // No file just means no runs yet.
+        let Some(Ok(bytes)) = leaderboard_path().map(std::fs::read) else {
+            return Self::default();
+        };
+
+        ron::de::from_bytes(&bytes).unwrap_or_else(|error| {
+            warn!("Couldn't read the endless leaderboard: {error}");
+            Self::default()
+        })
+    }
+
+    #[cfg(target_family = "wasm")]
+    fn load() -> Self {
+        Self::default()
+    }
+
+    #[cfg(not(target_family = "wasm"))]
+    fn save(&self) {
+        let Some(path) = leaderboard_path() else {
+            warn!("Couldn't save the endless leaderboard: no data directory");
+            return;
+        };
+
+        let result = ron::ser::to_string(self)
+            .map_err(|error| error.to_string())
+            .and_then(|ron| {
+                if let Some(directory) = path.parent() {
+                    std::fs::create_dir_all(directory).map_err(|error| error.to_string())?;
+                }
+
+                std::fs::write(&path, ron).map_err(|error| error.to_string())
+            });
+
+        if let Err(error) = result {
+            warn!("Couldn't save the endless leaderboard: {error}");
+        }
+    }
+
+    #[cfg(target_family = "wasm")]
+    fn save(&self) {}
+}
+
+/// Seconds as minutes and seconds, such as `2:05`.
+pub fn format_time(seconds: f32) -> String {
+    let seconds = seconds as u32;
+
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+pub struct LeaderboardPlugin;
+
+impl Plugin for LeaderboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(EndlessLeaderboard::load());
+    }
+}
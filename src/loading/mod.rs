@@ -0,0 +1,132 @@
+use bevy::{asset::LoadState, ecs::system::EntityCommands, prelude::*};
+
+use crate::app_state::AppState;
+use crate::asset_handles::{AssetHandles, initialize_asset_handles};
+use crate::colors::GOLD;
+use crate::widgets::{self, ProgressBar};
+
+pub struct LoadingPlugin;
+
+#[derive(Component)]
+struct LoadingScreen;
+
+/// The paths of every asset that failed to load.
+#[derive(Default, Resource)]
+struct LoadingFailures(Vec<String>);
+
+fn check_loading(
+    asset_handles: Res<AssetHandles>,
+    asset_server: Res<AssetServer>,
+    mut failures: ResMut<LoadingFailures>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut progress_query: Query<&mut ProgressBar>,
+) {
+    let mut loaded = 0;
+    let mut total = 0;
+
+    for id in asset_handles.loaded_ids() {
+        total += 1;
+
+        if asset_server.is_loaded_with_dependencies(id) {
+            loaded += 1;
+            continue;
+        }
+
+        if let Some(LoadState::Failed(error)) = asset_server.get_load_state(id) {
+            let path = asset_server
+                .get_path(id)
+                .map_or_else(|| format!("{id:?}"), |path| path.to_string());
+
+            if !failures.0.contains(&path) {
+                error!("Failed to load {path}: {error}");
+                failures.0.push(path);
+            }
+        }
+    }
+
+    if let Ok(mut progress) = progress_query.get_single_mut() {
+        progress.0 = if total == 0 {
+            1.0
+        } else {
+            loaded as f32 / total as f32
+        };
+    }
+
+    if !failures.0.is_empty() {
+        next_state.set(AppState::LoadingFailed);
+    } else if loaded == total && asset_handles.is_ready() {
+        next_state.set(AppState::Menu);
+    }
+}
+
+fn destroy_loading(mut commands: Commands, query: Query<Entity, With<LoadingScreen>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn loading_screen<'a>(commands: &'a mut Commands) -> EntityCommands<'a> {
+    commands.spawn((
+        BackgroundColor(Color::BLACK),
+        LoadingScreen,
+        Node {
+            align_items: AlignItems::Center,
+            display: Display::Flex,
+            flex_direction: FlexDirection::Column,
+            height: Val::Vh(100.0),
+            justify_content: JustifyContent::Center,
+            row_gap: Val::Px(16.0),
+            width: Val::Vw(100.0),
+            ..default()
+        },
+    ))
+}
+
+fn setup_loading(
+    asset_handles: Res<AssetHandles>,
+    mut commands: Commands,
+    query: Query<(), With<LoadingScreen>>,
+) {
+    // Wait for the manifest, so the screen can use its font.
+    if !asset_handles.is_ready() || !query.is_empty() {
+        return;
+    }
+
+    loading_screen(&mut commands).with_children(|parent| {
+        widgets::label(parent, &asset_handles, "Loading...");
+        widgets::progress_bar(parent);
+    });
+}
+
+/// Uses Bevy's built-in font rather than the game's, which may be one of the failures.
+fn setup_loading_failed(mut commands: Commands, failures: Res<LoadingFailures>) {
+    loading_screen(&mut commands).with_children(|parent| {
+        parent.spawn((
+            Text::new("Failed to load:"),
+            TextColor(Color::WHITE),
+            TextFont::default(),
+        ));
+
+        for path in failures.0.iter() {
+            parent.spawn((
+                Text::new(path.clone()),
+                TextColor(GOLD),
+                TextFont::default(),
+            ));
+        }
+    });
+}
+
+impl Plugin for LoadingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnExit(AppState::Loading), destroy_loading);
+        app.add_systems(OnEnter(AppState::LoadingFailed), setup_loading_failed);
+        app.add_systems(
+            Update,
+            (setup_loading, check_loading)
+                .after(initialize_asset_handles)
+                .run_if(in_state(AppState::Loading)),
+        );
+        app.init_resource::<LoadingFailures>();
+    }
+}
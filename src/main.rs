@@ -1,14 +1,25 @@
 mod action;
+mod animation_controller;
 mod app_state;
+mod arena_map;
 mod asset_handles;
+mod autotile;
 mod collision;
 mod colors;
+mod focus;
 mod game;
+mod game_mode;
 mod health;
+mod leaderboard;
+mod loading;
+mod manifest;
 mod menu;
+mod settings;
 mod simple_animations;
+mod widgets;
 
-use action::Action;
+use action::{default_input_map, Action};
+use animation_controller::AnimationControllerPlugin;
 use app_state::AppState;
 use asset_handles::AssetHandlesPlugin;
 use bevy::{asset::AssetMetaCheck, log::LogPlugin, prelude::*, render::camera::ScalingMode};
@@ -16,11 +27,17 @@ use bevy_ecs_tilemap::prelude::*;
 use bevy_prng::WyRand;
 use bevy_rand::prelude::EntropyPlugin;
 use collision::CollisionPlugin;
+use focus::FocusPlugin;
 use game::GamePlugin;
+use health::HealthPlugin;
+use leaderboard::LeaderboardPlugin;
 use leafwing_input_manager::prelude::*;
+use loading::LoadingPlugin;
 use menu::MenuPlugin;
+use settings::SettingsPlugin;
 use simple_animations::SimpleAnimationsPlugin;
 use tracing::Level;
+use widgets::WidgetsPlugin;
 
 fn setup(mut commands: Commands) {
     commands.spawn((
@@ -39,6 +56,7 @@ fn main() {
     let mut app = App::new();
 
     app.add_plugins((
+        AnimationControllerPlugin,
         AssetHandlesPlugin,
         CollisionPlugin,
         DefaultPlugins
@@ -61,14 +79,24 @@ fn main() {
                 ..default()
             }),
         EntropyPlugin::<WyRand>::default(),
+        FocusPlugin,
         GamePlugin,
+        HealthPlugin,
         InputManagerPlugin::<Action>::default(),
+        // Plugin tuples only go up to 15, so these two are grouped.
+        (LeaderboardPlugin, LoadingPlugin),
         MenuPlugin,
+        SettingsPlugin,
         SimpleAnimationsPlugin,
         TilemapPlugin,
+        WidgetsPlugin,
     ));
     app.add_systems(Startup, setup);
     app.init_state::<AppState>();
 
+    // Menus read input from a global action state rather than the player's.
+    app.init_resource::<ActionState<Action>>();
+    app.insert_resource(default_input_map());
+
     app.run();
 }
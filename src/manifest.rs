@@ -0,0 +1,85 @@
+use bevy::{
+    asset::{AssetLoader, LoadContext, io::Reader},
+    prelude::*,
+    utils::HashMap,
+};
+use serde::Deserialize;
+
+use crate::animation_controller::AnimationControllerDef;
+use crate::autotile::AutotileRules;
+use crate::simple_animations::SpriteClip;
+
+pub const MANIFEST_PATH: &str = "game.manifest.ron";
+
+/// A sprite sheet cut into a grid of equally sized frames.
+#[derive(Debug, Deserialize)]
+pub struct TextureAtlasEntry {
+    pub path: String,
+    pub tile_size: (u32, u32),
+    pub columns: u32,
+    pub rows: u32,
+    #[serde(default)]
+    pub padding: Option<(u32, u32)>,
+    #[serde(default)]
+    pub offset: Option<(u32, u32)>,
+}
+
+impl TextureAtlasEntry {
+    pub fn layout(&self) -> TextureAtlasLayout {
+        TextureAtlasLayout::from_grid(
+            self.tile_size.into(),
+            self.columns,
+            self.rows,
+            self.padding.map(UVec2::from),
+            self.offset.map(UVec2::from),
+        )
+    }
+}
+
+/// Describes every asset the game loads, keyed by the names used to look them up in code.
+#[derive(Asset, Debug, Deserialize, TypePath)]
+pub struct Manifest {
+    #[serde(default)]
+    pub animation_controllers: HashMap<String, AnimationControllerDef>,
+    #[serde(default)]
+    pub animations: HashMap<String, SpriteClip>,
+    /// Hand-authored arenas, mixed in with the generated ones.
+    #[serde(default)]
+    pub arenas: HashMap<String, String>,
+    #[serde(default)]
+    pub autotile_rules: HashMap<String, AutotileRules>,
+    #[serde(default)]
+    pub fonts: HashMap<String, String>,
+    #[serde(default)]
+    pub images: HashMap<String, String>,
+    #[serde(default)]
+    pub sounds: HashMap<String, String>,
+    #[serde(default)]
+    pub texture_atlases: HashMap<String, TextureAtlasEntry>,
+}
+
+#[derive(Default)]
+pub struct ManifestLoader;
+
+impl AssetLoader for ManifestLoader {
+    type Asset = Manifest;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+
+        reader.read_to_end(&mut bytes).await?;
+
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["manifest.ron"]
+    }
+}
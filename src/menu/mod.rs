@@ -2,68 +2,70 @@ use bevy::prelude::*;
 
 use crate::app_state::AppState;
 use crate::asset_handles::AssetHandles;
-use crate::colors::DARK_GRAY;
+use crate::focus::{AutoFocus, FocusActivated};
+use crate::game_mode::GameMode;
+use crate::leaderboard::{EndlessLeaderboard, format_time};
+use crate::settings::Settings;
+use crate::widgets::{self, Toggle};
+
+/// How many of the best endless times are listed.
+const LEADERBOARD_ROWS: usize = 5;
 
 pub struct MenuPlugin;
 
+#[derive(Component)]
+struct HardcoreToggle;
+
 #[derive(Component)]
 struct Menu;
 
-fn animate_buttons(
-    mut button_query: Query<
-        (
-            &mut BackgroundColor,
-            &mut BorderColor,
-            &Children,
-            &Interaction,
-        ),
-        (Changed<Interaction>, With<Button>),
-    >,
-    mut text_query: Query<&mut TextColor>,
-) {
-    for (mut background_color, mut border_color, children, interaction) in button_query.iter_mut() {
-        let Ok(mut text_color) = text_query.get_mut(children[0]) else {
-            continue;
-        };
-
-        match *interaction {
-            Interaction::Hovered => {
-                background_color.0 = Color::BLACK;
-                border_color.0 = DARK_GRAY;
-                text_color.0 = DARK_GRAY;
-            }
-            Interaction::None => {
-                background_color.0 = Color::BLACK;
-                border_color.0 = Color::WHITE;
-                text_color.0 = Color::WHITE;
-            }
-            Interaction::Pressed => {
-                background_color.0 = Color::WHITE;
-                border_color.0 = Color::WHITE;
-                text_color.0 = Color::BLACK;
-            }
-        };
-    }
-}
+#[derive(Component)]
+struct OpenWorldToggle;
+
+/// Starts a run of the given mode.
+#[derive(Component)]
+struct StartGameButton(GameMode);
 
 fn start_game_button(
-    button_query: Query<&Interaction, (Changed<Interaction>, With<Button>)>,
+    mut activated_events: EventReader<FocusActivated>,
+    button_query: Query<&StartGameButton>,
+    mut game_mode: ResMut<GameMode>,
     mut next_state: ResMut<NextState<AppState>>,
 ) {
-    for interaction in button_query.iter() {
-        if *interaction == Interaction::Pressed {
+    for FocusActivated(entity) in activated_events.read() {
+        if let Ok(StartGameButton(mode)) = button_query.get(*entity) {
+            *game_mode = *mode;
             next_state.set(AppState::Game);
         }
     }
 }
 
+fn menu_settings(
+    hardcore_query: Query<&Toggle, (Changed<Toggle>, With<HardcoreToggle>)>,
+    open_world_query: Query<&Toggle, (Changed<Toggle>, With<OpenWorldToggle>)>,
+    mut settings: ResMut<Settings>,
+) {
+    if let Ok(toggle) = hardcore_query.get_single() {
+        settings.hardcore = toggle.value;
+    }
+
+    if let Ok(toggle) = open_world_query.get_single() {
+        settings.open_world = toggle.value;
+    }
+}
+
 fn destroy_menu(mut commands: Commands, query: Query<Entity, With<Menu>>) {
     for entity in query.iter() {
         commands.entity(entity).despawn_recursive();
     }
 }
 
-fn setup_menu(asset_handles: Res<AssetHandles>, asset_server: Res<AssetServer>, mut commands: Commands) {
+fn setup_menu(
+    asset_handles: Res<AssetHandles>,
+    mut commands: Commands,
+    leaderboard: Res<EndlessLeaderboard>,
+    settings: Res<Settings>,
+) {
     commands
         .spawn((
             BackgroundColor(Color::BLACK),
@@ -88,14 +90,7 @@ fn setup_menu(asset_handles: Res<AssetHandles>, asset_server: Res<AssetServer>,
                     ..default()
                 })
                 .with_children(|parent| {
-                    parent.spawn((
-                        Text::new("Keep the Keep Moving!"),
-                        TextColor(Color::WHITE),
-                        TextFont {
-                            font: asset_handles.font_map.get("default").unwrap().clone(),
-                            ..default()
-                        },
-                    ));
+                    widgets::label(parent, &asset_handles, "Keep the Keep Moving!");
                 });
             parent.spawn(Node {
                 display: Display::Flex,
@@ -104,7 +99,7 @@ fn setup_menu(asset_handles: Res<AssetHandles>, asset_server: Res<AssetServer>,
                 ..default()
             }).with_children(|parent| {
                 parent.spawn((
-                    ImageNode::new(asset_server.load("sprites/title.png")),
+                    ImageNode::new(asset_handles.image("title")),
                     Node {
                         height: Val::VMin(70.0),
                         width: Val::VMin(70.0),
@@ -115,6 +110,7 @@ fn setup_menu(asset_handles: Res<AssetHandles>, asset_server: Res<AssetServer>,
             parent
                 .spawn(Node {
                     align_items: AlignItems::Center,
+                    column_gap: Val::Px(40.0),
                     display: Display::Flex,
                     flex_grow: 1.0,
                     justify_content: JustifyContent::Center,
@@ -122,25 +118,32 @@ fn setup_menu(asset_handles: Res<AssetHandles>, asset_server: Res<AssetServer>,
                     ..default()
                 })
                 .with_children(|parent| {
-                    parent
-                        .spawn((
-                            BackgroundColor(Color::WHITE),
-                            Button,
-                            Node {
-                                border: UiRect::all(Val::Px(5.0)),
-                                padding: UiRect::all(Val::Px(5.0)),
-                                ..default()
-                            },
-                            BorderColor(Color::WHITE),
-                        ))
-                        .with_child((
-                            Text::new("Start Game"),
-                            TextColor(Color::WHITE),
-                            TextFont {
-                                font: asset_handles.font_map.get("default").unwrap().clone(),
-                                ..default()
-                            },
-                        ));
+                    widgets::list(parent).with_children(|parent| {
+                        widgets::button(parent, &asset_handles, "Start Game")
+                            .insert((AutoFocus, StartGameButton(GameMode::Waves)));
+                        widgets::button(parent, &asset_handles, "Endless")
+                            .insert(StartGameButton(GameMode::Endless));
+                        widgets::toggle(parent, &asset_handles, "Hardcore", settings.hardcore)
+                            .insert(HardcoreToggle);
+                        widgets::toggle(parent, &asset_handles, "Open World", settings.open_world)
+                            .insert(OpenWorldToggle);
+                    });
+
+                    if leaderboard.times.is_empty() {
+                        return;
+                    }
+
+                    widgets::list(parent).with_children(|parent| {
+                        widgets::label(parent, &asset_handles, "Best Endless Times");
+
+                        for (place, time) in leaderboard.times.iter().take(LEADERBOARD_ROWS).enumerate() {
+                            widgets::label(
+                                parent,
+                                &asset_handles,
+                                format!("{}. {}", place + 1, format_time(*time)),
+                            );
+                        }
+                    });
                 });
         });
 }
@@ -151,7 +154,7 @@ impl Plugin for MenuPlugin {
         app.add_systems(OnExit(AppState::Menu), destroy_menu);
         app.add_systems(
             Update,
-            (animate_buttons, start_game_button).run_if(in_state(AppState::Menu)),
+            (menu_settings, start_game_button).run_if(in_state(AppState::Menu)),
         );
     }
 }
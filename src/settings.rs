@@ -0,0 +1,55 @@
+use bevy::{audio::*, prelude::*};
+
+const DEFAULT_VOLUME: f32 = 5.0;
+pub const MAX_VOLUME: f32 = 10.0;
+
+#[derive(Resource)]
+pub struct Settings {
+    /// Driving into a wall destroys the keep outright, rather than bouncing it off.
+    pub hardcore: bool,
+    pub music: bool,
+    /// Waves are fought across an endless world streamed in around the keep, rather than in a
+    /// walled arena.
+    pub open_world: bool,
+    pub volume: f32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            hardcore: false,
+            music: true,
+            open_world: false,
+            volume: DEFAULT_VOLUME,
+        }
+    }
+}
+
+impl Settings {
+    pub fn music_volume(&self) -> Volume {
+        if self.music {
+            Volume::new(self.volume / MAX_VOLUME)
+        } else {
+            Volume::ZERO
+        }
+    }
+}
+
+fn apply_volume(query: Query<&AudioSink>, settings: Res<Settings>) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    for sink in query.iter() {
+        sink.set_volume(settings.music_volume().get());
+    }
+}
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, apply_volume);
+        app.init_resource::<Settings>();
+    }
+}
@@ -1,39 +1,178 @@
 use bevy::prelude::*;
+use serde::Deserialize;
 
 const DEFAULT_ANIMATION_SPEED: f32 = 0.1;
 
+fn default_animation_speed() -> f32 {
+    DEFAULT_ANIMATION_SPEED
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq)]
+pub enum ClipMode {
+    #[default]
+    Loop,
+    /// Plays through once and stops on the last frame, sending `AnimationFinished`.
+    Once,
+    /// Plays forwards then backwards, forever.
+    PingPong,
+}
+
+/// A named sequence of frames from a sprite sheet.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+pub struct SpriteClip {
+    #[serde(default = "default_animation_speed")]
+    pub frame_duration: f32,
+    pub frames: Vec<usize>,
+    #[serde(default)]
+    pub mode: ClipMode,
+    /// Filled in from the clip's key in the manifest.
+    #[serde(skip)]
+    pub name: String,
+}
+
+/// Sent when a `ClipMode::Once` clip plays its last frame.
+#[derive(Event)]
+pub struct AnimationFinished {
+    pub clip: String,
+    pub entity: Entity,
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq, SystemSet)]
+pub struct AnimationSet;
+
 #[derive(Component)]
 #[require(Sprite)]
 pub struct SimpleAnimation {
     pub animation_timer: Timer,
+    pub clip: SpriteClip,
     pub current_frame_index: usize,
-    pub frames: Vec<usize>,
+    finished: bool,
+    queued: Option<SpriteClip>,
+    reversing: bool,
 }
 
 impl Default for SimpleAnimation {
     fn default() -> Self {
         Self {
             animation_timer: Timer::from_seconds(DEFAULT_ANIMATION_SPEED, TimerMode::Repeating),
+            clip: SpriteClip::default(),
             current_frame_index: 0,
-            frames: Vec::new(),
+            finished: false,
+            queued: None,
+            reversing: false,
         }
     }
 }
 
-fn animate(mut query: Query<(&mut SimpleAnimation, &mut Sprite)>, time: Res<Time>) {
-    for (mut simple_animation, mut sprite) in query.iter_mut() {
+impl SimpleAnimation {
+    pub fn current_frame(&self) -> Option<usize> {
+        self.clip.frames.get(self.current_frame_index).copied()
+    }
+
+    /// Whether a one-shot clip has played through.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Starts `clip` from its first frame, unless it is already playing. Any queued clip is
+    /// dropped.
+    pub fn play(&mut self, clip: SpriteClip) {
+        self.queued = None;
+
+        if self.clip.name == clip.name && !clip.name.is_empty() {
+            return;
+        }
+
+        self.restart(clip);
+    }
+
+    /// Starts `clip`, then switches to `next` once it finishes. `clip` should play once.
+    pub fn play_then(&mut self, clip: SpriteClip, next: SpriteClip) {
+        self.play(clip);
+        self.queued = Some(next);
+    }
+
+    fn restart(&mut self, clip: SpriteClip) {
+        self.animation_timer = Timer::from_seconds(clip.frame_duration, TimerMode::Repeating);
+        self.clip = clip;
+        self.current_frame_index = 0;
+        self.finished = false;
+        self.reversing = false;
+    }
+
+    /// Moves to the next frame, returning whether a one-shot clip just finished.
+    fn advance(&mut self) -> bool {
+        let last_frame_index = self.clip.frames.len().saturating_sub(1);
+
+        match self.clip.mode {
+            ClipMode::Loop => {
+                self.current_frame_index += 1;
+                if self.current_frame_index > last_frame_index {
+                    self.current_frame_index = 0;
+                }
+            }
+            ClipMode::Once => {
+                if self.finished {
+                    return false;
+                }
+
+                if self.current_frame_index < last_frame_index {
+                    self.current_frame_index += 1;
+                } else {
+                    self.finished = true;
+                    return true;
+                }
+            }
+            ClipMode::PingPong => {
+                if self.reversing && self.current_frame_index == 0 {
+                    self.reversing = false;
+                } else if !self.reversing && self.current_frame_index >= last_frame_index {
+                    self.reversing = true;
+                }
+
+                if self.reversing {
+                    self.current_frame_index = self.current_frame_index.saturating_sub(1);
+                } else {
+                    self.current_frame_index = (self.current_frame_index + 1).min(last_frame_index);
+                }
+            }
+        }
+
+        false
+    }
+}
+
+fn animate(
+    mut finished_events: EventWriter<AnimationFinished>,
+    mut query: Query<(Entity, &mut SimpleAnimation, &mut Sprite)>,
+    time: Res<Time>,
+) {
+    for (entity, mut simple_animation, mut sprite) in query.iter_mut() {
         let Some(texture_atlas) = sprite.texture_atlas.as_mut() else {
             continue;
         };
 
         simple_animation.animation_timer.tick(time.delta());
 
-        if simple_animation.animation_timer.just_finished() {
-            simple_animation.current_frame_index += 1;
-            if simple_animation.current_frame_index >= simple_animation.frames.len() {
-                simple_animation.current_frame_index = 0;
+        for _ in 0..simple_animation.animation_timer.times_finished_this_tick() {
+            if !simple_animation.advance() {
+                continue;
+            }
+
+            finished_events.send(AnimationFinished {
+                clip: simple_animation.clip.name.clone(),
+                entity,
+            });
+
+            if let Some(next) = simple_animation.queued.take() {
+                simple_animation.restart(next);
             }
-            texture_atlas.index = simple_animation.frames[simple_animation.current_frame_index];
+        }
+
+        if let Some(frame) = simple_animation.current_frame()
+            && texture_atlas.index != frame
+        {
+            texture_atlas.index = frame;
         }
     }
 }
@@ -42,6 +181,7 @@ pub struct SimpleAnimationsPlugin;
 
 impl Plugin for SimpleAnimationsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(PreUpdate, animate);
+        app.add_event::<AnimationFinished>();
+        app.add_systems(PreUpdate, animate.in_set(AnimationSet));
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,349 @@
+use bevy::{ecs::system::EntityCommands, prelude::*};
+use leafwing_input_manager::prelude::*;
+
+use crate::action::Action;
+use crate::asset_handles::AssetHandles;
+use crate::focus::{Focus, FocusActivated, FocusTrap, Focusable, HorizontalInput};
+
+const FONT_KEY: &str = "default";
+
+/// A focusable value that is nudged with `MoveLeft`/`MoveRight`, or cycled by activating it.
+#[derive(Component)]
+#[require(HorizontalInput)]
+pub struct Slider {
+    pub label: String,
+    pub max: f32,
+    pub min: f32,
+    pub step: f32,
+    pub value: f32,
+}
+
+impl Slider {
+    fn text(&self) -> String {
+        format!("{} < {} >", self.label, self.value)
+    }
+}
+
+/// How full a progress bar is, from 0 to 1.
+#[derive(Component, Default)]
+pub struct ProgressBar(pub f32);
+
+#[derive(Component)]
+struct ProgressBarFill;
+
+/// A focusable on/off switch that flips when activated.
+#[derive(Component)]
+pub struct Toggle {
+    pub label: String,
+    pub value: bool,
+}
+
+impl Toggle {
+    fn text(&self) -> String {
+        format!("{}: {}", self.label, if self.value { "On" } else { "Off" })
+    }
+}
+
+pub fn text_font(asset_handles: &AssetHandles) -> TextFont {
+    TextFont {
+        font: asset_handles.font(FONT_KEY),
+        ..default()
+    }
+}
+
+/// Plain white text in the pixel font.
+pub fn label<'a>(
+    parent: &'a mut ChildBuilder,
+    asset_handles: &AssetHandles,
+    value: impl Into<String>,
+) -> EntityCommands<'a> {
+    parent.spawn((
+        Text::new(value),
+        TextColor(Color::WHITE),
+        text_font(asset_handles),
+    ))
+}
+
+/// A bordered, focusable button. Listen for `FocusActivated` to react to it.
+pub fn button<'a>(
+    parent: &'a mut ChildBuilder,
+    asset_handles: &AssetHandles,
+    value: impl Into<String>,
+) -> EntityCommands<'a> {
+    let mut button = parent.spawn((
+        BackgroundColor(Color::BLACK),
+        BorderColor(Color::WHITE),
+        Button,
+        Focusable,
+        Node {
+            border: UiRect::all(Val::Px(5.0)),
+            justify_content: JustifyContent::Center,
+            padding: UiRect::all(Val::Px(5.0)),
+            ..default()
+        },
+    ));
+
+    button.with_child((
+        Text::new(value),
+        TextColor(Color::WHITE),
+        text_font(asset_handles),
+    ));
+
+    button
+}
+
+/// A button showing a labelled value that can be adjusted between `min` and `max`.
+pub fn slider<'a>(
+    parent: &'a mut ChildBuilder,
+    asset_handles: &AssetHandles,
+    slider: Slider,
+) -> EntityCommands<'a> {
+    let text = slider.text();
+    let mut button = button(parent, asset_handles, text);
+
+    button.insert(slider);
+
+    button
+}
+
+/// A button showing a labelled on/off value.
+pub fn toggle<'a>(
+    parent: &'a mut ChildBuilder,
+    asset_handles: &AssetHandles,
+    value: impl Into<String>,
+    on: bool,
+) -> EntityCommands<'a> {
+    let toggle = Toggle {
+        label: value.into(),
+        value: on,
+    };
+    let text = toggle.text();
+    let mut button = button(parent, asset_handles, text);
+
+    button.insert(toggle);
+
+    button
+}
+
+/// A white bar filling up a bordered track. Set `ProgressBar` to change how full it is.
+pub fn progress_bar<'a>(parent: &'a mut ChildBuilder) -> EntityCommands<'a> {
+    let mut progress_bar = parent.spawn((
+        BorderColor(Color::WHITE),
+        Node {
+            border: UiRect::all(Val::Px(5.0)),
+            height: Val::Px(30.0),
+            padding: UiRect::all(Val::Px(5.0)),
+            width: Val::Vw(50.0),
+            ..default()
+        },
+        ProgressBar::default(),
+    ));
+
+    progress_bar.with_child((
+        BackgroundColor(Color::WHITE),
+        Node {
+            height: Val::Percent(100.0),
+            width: Val::Percent(0.0),
+            ..default()
+        },
+        ProgressBarFill,
+    ));
+
+    progress_bar
+}
+
+/// A vertical stack of widgets, such as the buttons of a menu.
+pub fn list<'a>(parent: &'a mut ChildBuilder) -> EntityCommands<'a> {
+    parent.spawn(Node {
+        align_items: AlignItems::Stretch,
+        display: Display::Flex,
+        flex_direction: FlexDirection::Column,
+        row_gap: Val::Px(10.0),
+        ..default()
+    })
+}
+
+/// A black strip of evenly spaced widgets, as used along the edges of the HUD.
+pub fn bar<'a>(parent: &'a mut ChildBuilder) -> EntityCommands<'a> {
+    parent.spawn((
+        BackgroundColor(Color::BLACK),
+        Node {
+            align_items: AlignItems::Center,
+            display: Display::Flex,
+            justify_content: JustifyContent::SpaceBetween,
+            padding: UiRect::all(Val::Px(5.0)),
+            ..default()
+        },
+    ))
+}
+
+/// A full-screen overlay with a titled panel in the middle. The returned entity is the overlay,
+/// so despawning it recursively removes the whole modal.
+pub fn modal<'a>(
+    commands: &'a mut Commands,
+    asset_handles: &AssetHandles,
+    title: impl Into<String>,
+    children: impl FnOnce(&mut ChildBuilder),
+) -> EntityCommands<'a> {
+    let title = title.into();
+    let mut overlay = commands.spawn((
+        BackgroundColor(Color::BLACK.with_alpha(0.8)),
+        FocusTrap,
+        GlobalZIndex(1),
+        Node {
+            align_items: AlignItems::Center,
+            display: Display::Flex,
+            height: Val::Vh(100.0),
+            justify_content: JustifyContent::Center,
+            position_type: PositionType::Absolute,
+            width: Val::Vw(100.0),
+            ..default()
+        },
+    ));
+
+    overlay.with_children(|parent| {
+        parent
+            .spawn((
+                BackgroundColor(Color::BLACK),
+                BorderColor(Color::WHITE),
+                Node {
+                    align_items: AlignItems::Center,
+                    border: UiRect::all(Val::Px(5.0)),
+                    display: Display::Flex,
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(16.0)),
+                    row_gap: Val::Px(16.0),
+                    ..default()
+                },
+            ))
+            .with_children(|parent| {
+                label(parent, asset_handles, title);
+                children(parent);
+            });
+    });
+
+    overlay
+}
+
+/// A short message boxed in the middle of the screen, such as "Game Over!".
+pub fn banner<'a>(
+    commands: &'a mut Commands,
+    asset_handles: &AssetHandles,
+    value: impl Into<String>,
+) -> EntityCommands<'a> {
+    let mut banner = commands.spawn((
+        BackgroundColor(Color::BLACK),
+        Node {
+            align_items: AlignItems::Center,
+            display: Display::Flex,
+            justify_content: JustifyContent::Center,
+            left: Val::Percent(50.0),
+            padding: UiRect::all(Val::Px(16.0)),
+            position_type: PositionType::Absolute,
+            top: Val::Percent(50.0),
+            ..default()
+        },
+    ));
+
+    banner.with_child((
+        Text::new(value),
+        TextColor(Color::WHITE),
+        text_font(asset_handles),
+    ));
+
+    banner
+}
+
+fn adjust_sliders(
+    action_state: Res<ActionState<Action>>,
+    focus: Res<Focus>,
+    mut slider_query: Query<&mut Slider>,
+) {
+    let Some(entity) = focus.0 else {
+        return;
+    };
+
+    let Ok(mut slider) = slider_query.get_mut(entity) else {
+        return;
+    };
+
+    let mut value = slider.value;
+
+    if action_state.just_pressed(&Action::MoveLeft) {
+        value -= slider.step;
+    }
+
+    if action_state.just_pressed(&Action::MoveRight) {
+        value += slider.step;
+    }
+
+    let value = value.clamp(slider.min, slider.max);
+
+    if slider.value != value {
+        slider.value = value;
+    }
+}
+
+fn activate_widgets(
+    mut activated_events: EventReader<FocusActivated>,
+    mut slider_query: Query<&mut Slider>,
+    mut toggle_query: Query<&mut Toggle>,
+) {
+    for FocusActivated(entity) in activated_events.read() {
+        if let Ok(mut slider) = slider_query.get_mut(*entity) {
+            // Activating a slider cycles it, so it is usable with only a mouse.
+            slider.value = if slider.value >= slider.max {
+                slider.min
+            } else {
+                (slider.value + slider.step).min(slider.max)
+            };
+        }
+
+        if let Ok(mut toggle) = toggle_query.get_mut(*entity) {
+            toggle.value = !toggle.value;
+        }
+    }
+}
+
+fn update_progress_bars(
+    mut fill_query: Query<&mut Node, With<ProgressBarFill>>,
+    progress_bar_query: Query<(&Children, &ProgressBar), Changed<ProgressBar>>,
+) {
+    for (children, progress_bar) in progress_bar_query.iter() {
+        if let Ok(mut node) = fill_query.get_mut(children[0]) {
+            node.width = Val::Percent(progress_bar.0.clamp(0.0, 1.0) * 100.0);
+        }
+    }
+}
+
+fn update_widget_text(
+    slider_query: Query<(&Children, &Slider), Changed<Slider>>,
+    mut text_query: Query<&mut Text>,
+    toggle_query: Query<(&Children, &Toggle), Changed<Toggle>>,
+) {
+    for (children, slider) in slider_query.iter() {
+        if let Ok(mut text) = text_query.get_mut(children[0]) {
+            text.0 = slider.text();
+        }
+    }
+
+    for (children, toggle) in toggle_query.iter() {
+        if let Ok(mut text) = text_query.get_mut(children[0]) {
+            text.0 = toggle.text();
+        }
+    }
+}
+
+pub struct WidgetsPlugin;
+
+impl Plugin for WidgetsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                (adjust_sliders, activate_widgets, update_widget_text).chain(),
+                update_progress_bars,
+            ),
+        );
+    }
+}